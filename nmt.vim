@@ -2,10 +2,10 @@ syntax match Types /@\[.\+\]\|@[_a-zA-Z]\+/
 syntax match Numbers /[0-9]\+/
 syntax match Comments /[~].\+/
 syntax match StringLiterals /".\+"/
-syntax match DeclearKeywords /var\|return\|func/
-syntax match ConditionalKeywords /if\|else\|while\|include/
+syntax match DeclearKeywords /var\|return\|func\|struct\|enum\|as\|sizeof\|len/
+syntax match ConditionalKeywords /if\|else\|while\|for\|in\|include\|match\|not/
 syntax match Semicolon /[;]/
-syntax match Print /print/
+syntax match Print /print\|put\|panic\|assert/
 
 highlight Types guifg=#7dcfff
 highlight Numbers guifg=#d08770
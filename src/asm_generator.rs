@@ -8,6 +8,7 @@ pub fn x86_64_nasm_generator(
     path: String,
     instruct_buf: Vec<String>,
     data_buf: Vec<String>,
+    bss_buf: Vec<String>,
 ) -> Result<(), Box<dyn Error>> {
     fs::create_dir_all("./build").unwrap();
     let out_name = get_program_name(path);
@@ -24,57 +25,262 @@ pub fn x86_64_nasm_generator(
             file.write_all(data.as_bytes())?;
         }
     }
+    if !bss_buf.is_empty() {
+        file.write_all(b"section .bss\n")?;
+        for bss in &bss_buf {
+            file.write_all(bss.as_bytes())?;
+        }
+    }
     file.write_all(b"\n")?;
 
     file.write_all(b"section .text\n")?;
     file.write_all(b"global _start\n")?;
-    file.write_all(b"print:\n")?;
+    // Runtime routines use the __nmt_ prefix that user functions can not have
+    // __nmt_print_num(value, base, flags) prints the value in base 10, 16 or 2
+    // flags: bit 0 appends a newline, bit 1 prints the value as signed
+    // __nmt_print_char(value, _, newline) prints the value as a character
+    // __nmt_print_float(xmm0, _, newline) prints a f64 with up to 6 decimal places
+    // inf, -inf and nan are printed by name and values from 2^63 up
+    // are printed as a mantissa and a power of ten e.g: 1.5e20
+    // __nmt_str_cmp(ptr1, len1, ptr2, len2) compares two strings byte by byte
+    // and returns -1, 0 or 1 like memcmp, a shorter prefix is smaller
+    // __nmt_panic(_, message, len) writes the message to stderr and exits with 1
+    file.write_all(b"__nmt_print_num:\n")?;
     file.write_all(b"    push    rbp\n")?;
     file.write_all(b"    mov     rbp, rsp\n")?;
-    file.write_all(b"    sub     rsp, 64\n")?;
-    file.write_all(b"    mov     qword [rbp-56], rdi\n")?;
-    file.write_all(b"    mov     qword [rbp-8], 1\n")?;
-    file.write_all(b"    mov     eax, 32\n")?;
-    file.write_all(b"    sub     rax, qword [rbp-8]\n")?;
-    file.write_all(b"    mov     BYTE [rbp-48+rax], 10\n")?;
-    file.write_all(b".L3:\n")?;
-    file.write_all(b"    mov     rcx, qword [rbp-56]\n")?;
-    file.write_all(b"    mov     rdx, -3689348814741910323\n")?;
-    file.write_all(b"    mov     rax, rcx\n")?;
-    file.write_all(b"    mul     rdx\n")?;
-    file.write_all(b"    shr     rdx, 3\n")?;
-    file.write_all(b"    mov     rax, rdx\n")?;
-    file.write_all(b"    sal     rax, 2\n")?;
-    file.write_all(b"    add     rax, rdx\n")?;
-    file.write_all(b"    add     rax, rax\n")?;
-    file.write_all(b"    sub     rcx, rax\n")?;
-    file.write_all(b"    mov     rdx, rcx\n")?;
-    file.write_all(b"    mov     eax, edx\n")?;
-    file.write_all(b"    lea     edx, [rax+48]\n")?;
-    file.write_all(b"    mov     eax, 31\n")?;
-    file.write_all(b"    sub     rax, qword [rbp-8]\n")?;
-    file.write_all(b"    mov     byte [rbp-48+rax], dl\n")?;
-    file.write_all(b"    add     qword [rbp-8], 1\n")?;
-    file.write_all(b"    mov     rax, qword [rbp-56]\n")?;
-    file.write_all(b"    mov     rdx, -3689348814741910323\n")?;
-    file.write_all(b"    mul     rdx\n")?;
-    file.write_all(b"    mov     rax, rdx\n")?;
-    file.write_all(b"    shr     rax, 3\n")?;
-    file.write_all(b"    mov     qword [rbp-56], rax\n")?;
-    file.write_all(b"    cmp     qword [rbp-56], 0\n")?;
-    file.write_all(b"    jne     .L3\n")?;
-    file.write_all(b"    mov     eax, 32\n")?;
-    file.write_all(b"    sub     rax, qword [rbp-8]\n")?;
-    file.write_all(b"    lea     rdx, [rbp-48]\n")?;
-    file.write_all(b"    add     rax, rdx\n")?;
-    file.write_all(b"    mov     rsi, rax\n")?;
-    file.write_all(b"    mov     rbx, qword [rbp-8]\n")?;
-    file.write_all(b"    mov     rdx, rbx\n")?;
+    file.write_all(b"    sub     rsp, 80\n")?;
+    file.write_all(b"    mov     rax, rdi\n")?;
+    file.write_all(b"    mov     rcx, rsi\n")?;
+    file.write_all(b"    mov     r8, rdx\n")?;
+    file.write_all(b"    lea     rsi, [rbp-1]\n")?;
+    file.write_all(b"    test    r8, 1\n")?;
+    file.write_all(b"    jz      .Lsign\n")?;
+    file.write_all(b"    mov     byte [rsi], 10\n")?;
+    file.write_all(b"    dec     rsi\n")?;
+    file.write_all(b".Lsign:\n")?;
+    file.write_all(b"    xor     r9, r9\n")?;
+    file.write_all(b"    test    r8, 2\n")?;
+    file.write_all(b"    jz      .Ldigit\n")?;
+    file.write_all(b"    test    rax, rax\n")?;
+    file.write_all(b"    jns     .Ldigit\n")?;
+    file.write_all(b"    neg     rax\n")?;
+    file.write_all(b"    mov     r9, 1\n")?;
+    file.write_all(b".Ldigit:\n")?;
+    file.write_all(b"    xor     rdx, rdx\n")?;
+    file.write_all(b"    div     rcx\n")?;
+    file.write_all(b"    add     dl, 48\n")?;
+    file.write_all(b"    cmp     dl, 57\n")?;
+    file.write_all(b"    jbe     .Lstore\n")?;
+    file.write_all(b"    add     dl, 39\n")?;
+    file.write_all(b".Lstore:\n")?;
+    file.write_all(b"    mov     byte [rsi], dl\n")?;
+    file.write_all(b"    dec     rsi\n")?;
+    file.write_all(b"    test    rax, rax\n")?;
+    file.write_all(b"    jnz     .Ldigit\n")?;
+    file.write_all(b"    cmp     rcx, 10\n")?;
+    file.write_all(b"    je      .Lminus\n")?;
+    file.write_all(b"    mov     byte [rsi], 120\n")?;
+    file.write_all(b"    cmp     rcx, 16\n")?;
+    file.write_all(b"    je      .Lprefix\n")?;
+    file.write_all(b"    mov     byte [rsi], 98\n")?;
+    file.write_all(b".Lprefix:\n")?;
+    file.write_all(b"    mov     byte [rsi-1], 48\n")?;
+    file.write_all(b"    sub     rsi, 2\n")?;
+    file.write_all(b".Lminus:\n")?;
+    file.write_all(b"    test    r9, r9\n")?;
+    file.write_all(b"    jz      .Lwrite\n")?;
+    file.write_all(b"    mov     byte [rsi], 45\n")?;
+    file.write_all(b"    dec     rsi\n")?;
+    file.write_all(b".Lwrite:\n")?;
+    file.write_all(b"    inc     rsi\n")?;
+    file.write_all(b"    mov     rdx, rbp\n")?;
+    file.write_all(b"    sub     rdx, rsi\n")?;
     file.write_all(b"    mov     rdi, 1\n")?;
     file.write_all(b"    mov     rax, 1\n")?;
     file.write_all(b"    syscall\n")?;
     file.write_all(b"    leave\n")?;
     file.write_all(b"    ret\n")?;
+    file.write_all(b"__nmt_print_char:\n")?;
+    file.write_all(b"    push    rbp\n")?;
+    file.write_all(b"    mov     rbp, rsp\n")?;
+    file.write_all(b"    sub     rsp, 16\n")?;
+    file.write_all(b"    mov     byte [rbp-2], dil\n")?;
+    file.write_all(b"    mov     byte [rbp-1], 10\n")?;
+    file.write_all(b"    lea     rdx, [rdx+1]\n")?;
+    file.write_all(b"    lea     rsi, [rbp-2]\n")?;
+    file.write_all(b"    mov     rdi, 1\n")?;
+    file.write_all(b"    mov     rax, 1\n")?;
+    file.write_all(b"    syscall\n")?;
+    file.write_all(b"    leave\n")?;
+    file.write_all(b"    ret\n")?;
+    file.write_all(b"__nmt_print_float:\n")?;
+    file.write_all(b"    push    rbp\n")?;
+    file.write_all(b"    mov     rbp, rsp\n")?;
+    file.write_all(b"    push    rbx\n")?;
+    file.write_all(b"    push    r12\n")?;
+    file.write_all(b"    push    r13\n")?;
+    file.write_all(b"    push    r14\n")?;
+    file.write_all(b"    push    r15\n")?;
+    file.write_all(b"    mov     r12, rdx\n")?;
+    file.write_all(b"    xor     r15, r15\n")?;
+    file.write_all(b"    movq    r13, xmm0\n")?;
+    file.write_all(b"    mov     rbx, r13\n")?;
+    file.write_all(b"    btr     r13, 63\n")?;
+    file.write_all(b"    mov     rax, 0x7ff0000000000000\n")?;
+    file.write_all(b"    cmp     r13, rax\n")?;
+    file.write_all(b"    ja      .Lnan\n")?;
+    file.write_all(b"    bt      rbx, 63\n")?;
+    file.write_all(b"    jnc     .Labs\n")?;
+    file.write_all(b"    mov     rdi, 45\n")?;
+    file.write_all(b"    xor     rdx, rdx\n")?;
+    file.write_all(b"    call    __nmt_print_char\n")?;
+    file.write_all(b".Labs:\n")?;
+    file.write_all(b"    mov     rax, 0x7ff0000000000000\n")?;
+    file.write_all(b"    cmp     r13, rax\n")?;
+    file.write_all(b"    je      .Linf\n")?;
+    file.write_all(b"    mov     rax, 0x43e0000000000000\n")?;
+    file.write_all(b"    cmp     r13, rax\n")?;
+    file.write_all(b"    jb      .Lsmall\n")?;
+    file.write_all(b"    movq    xmm0, r13\n")?;
+    file.write_all(b"    mov     rax, 10\n")?;
+    file.write_all(b"    cvtsi2sd xmm1, rax\n")?;
+    file.write_all(b".Lexponent:\n")?;
+    file.write_all(b"    divsd   xmm0, xmm1\n")?;
+    file.write_all(b"    inc     r15\n")?;
+    file.write_all(b"    ucomisd xmm0, xmm1\n")?;
+    file.write_all(b"    jae     .Lexponent\n")?;
+    file.write_all(b"    movq    r13, xmm0\n")?;
+    file.write_all(b".Lsmall:\n")?;
+    file.write_all(b"    movq    xmm0, r13\n")?;
+    file.write_all(b"    cvttsd2si rbx, xmm0\n")?;
+    file.write_all(b"    cvtsi2sd xmm1, rbx\n")?;
+    file.write_all(b"    subsd   xmm0, xmm1\n")?;
+    file.write_all(b"    mov     rax, 1000000\n")?;
+    file.write_all(b"    cvtsi2sd xmm1, rax\n")?;
+    file.write_all(b"    mulsd   xmm0, xmm1\n")?;
+    file.write_all(b"    cvtsd2si r13, xmm0\n")?;
+    file.write_all(b"    cmp     r13, 1000000\n")?;
+    file.write_all(b"    jl      .Linteger\n")?;
+    file.write_all(b"    inc     rbx\n")?;
+    file.write_all(b"    sub     r13, 1000000\n")?;
+    file.write_all(b".Linteger:\n")?;
+    file.write_all(b"    mov     rdi, rbx\n")?;
+    file.write_all(b"    mov     rsi, 10\n")?;
+    file.write_all(b"    xor     rdx, rdx\n")?;
+    file.write_all(b"    call    __nmt_print_num\n")?;
+    file.write_all(b"    mov     rdi, 46\n")?;
+    file.write_all(b"    xor     rdx, rdx\n")?;
+    file.write_all(b"    call    __nmt_print_char\n")?;
+    file.write_all(b"    mov     r14, 100000\n")?;
+    file.write_all(b".Ltrim:\n")?;
+    file.write_all(b"    cmp     r14, 1\n")?;
+    file.write_all(b"    jbe     .Lzeros\n")?;
+    file.write_all(b"    mov     rax, r13\n")?;
+    file.write_all(b"    xor     rdx, rdx\n")?;
+    file.write_all(b"    mov     rcx, 10\n")?;
+    file.write_all(b"    div     rcx\n")?;
+    file.write_all(b"    test    rdx, rdx\n")?;
+    file.write_all(b"    jnz     .Lzeros\n")?;
+    file.write_all(b"    mov     r13, rax\n")?;
+    file.write_all(b"    mov     rax, r14\n")?;
+    file.write_all(b"    xor     rdx, rdx\n")?;
+    file.write_all(b"    div     rcx\n")?;
+    file.write_all(b"    mov     r14, rax\n")?;
+    file.write_all(b"    jmp     .Ltrim\n")?;
+    file.write_all(b".Lzeros:\n")?;
+    file.write_all(b"    cmp     r14, 1\n")?;
+    file.write_all(b"    jbe     .Lfraction\n")?;
+    file.write_all(b"    cmp     r13, r14\n")?;
+    file.write_all(b"    jae     .Lfraction\n")?;
+    file.write_all(b"    mov     rdi, 48\n")?;
+    file.write_all(b"    xor     rdx, rdx\n")?;
+    file.write_all(b"    call    __nmt_print_char\n")?;
+    file.write_all(b"    mov     rax, r14\n")?;
+    file.write_all(b"    xor     rdx, rdx\n")?;
+    file.write_all(b"    mov     rcx, 10\n")?;
+    file.write_all(b"    div     rcx\n")?;
+    file.write_all(b"    mov     r14, rax\n")?;
+    file.write_all(b"    jmp     .Lzeros\n")?;
+    file.write_all(b".Lfraction:\n")?;
+    file.write_all(b"    mov     rdi, r13\n")?;
+    file.write_all(b"    mov     rsi, 10\n")?;
+    file.write_all(b"    mov     rdx, r12\n")?;
+    file.write_all(b"    and     rdx, 1\n")?;
+    file.write_all(b"    test    r15, r15\n")?;
+    file.write_all(b"    jz      .Lnumber\n")?;
+    file.write_all(b"    xor     rdx, rdx\n")?;
+    file.write_all(b".Lnumber:\n")?;
+    file.write_all(b"    call    __nmt_print_num\n")?;
+    file.write_all(b"    test    r15, r15\n")?;
+    file.write_all(b"    jz      .Ldone\n")?;
+    file.write_all(b"    mov     rdi, 101\n")?;
+    file.write_all(b"    xor     rdx, rdx\n")?;
+    file.write_all(b"    call    __nmt_print_char\n")?;
+    file.write_all(b"    mov     rdi, r15\n")?;
+    file.write_all(b"    mov     rsi, 10\n")?;
+    file.write_all(b"    mov     rdx, r12\n")?;
+    file.write_all(b"    and     rdx, 1\n")?;
+    file.write_all(b"    call    __nmt_print_num\n")?;
+    file.write_all(b"    jmp     .Ldone\n")?;
+    // the name is packed into rbx with its first letter in the low byte
+    file.write_all(b".Lnan:\n")?;
+    file.write_all(b"    mov     rbx, 0x6e616e\n")?;
+    file.write_all(b"    jmp     .Lname\n")?;
+    file.write_all(b".Linf:\n")?;
+    file.write_all(b"    mov     rbx, 0x666e69\n")?;
+    file.write_all(b".Lname:\n")?;
+    file.write_all(b"    movzx   rdi, bl\n")?;
+    file.write_all(b"    shr     rbx, 8\n")?;
+    file.write_all(b"    xor     rdx, rdx\n")?;
+    file.write_all(b"    test    rbx, rbx\n")?;
+    file.write_all(b"    jnz     .Lletter\n")?;
+    file.write_all(b"    mov     rdx, r12\n")?;
+    file.write_all(b"    and     rdx, 1\n")?;
+    file.write_all(b".Lletter:\n")?;
+    file.write_all(b"    call    __nmt_print_char\n")?;
+    file.write_all(b"    test    rbx, rbx\n")?;
+    file.write_all(b"    jnz     .Lname\n")?;
+    file.write_all(b".Ldone:\n")?;
+    file.write_all(b"    pop     r15\n")?;
+    file.write_all(b"    pop     r14\n")?;
+    file.write_all(b"    pop     r13\n")?;
+    file.write_all(b"    pop     r12\n")?;
+    file.write_all(b"    pop     rbx\n")?;
+    file.write_all(b"    leave\n")?;
+    file.write_all(b"    ret\n")?;
+    file.write_all(b"__nmt_str_cmp:\n")?;
+    file.write_all(b"    xor     r8, r8\n")?;
+    file.write_all(b".Lnext:\n")?;
+    file.write_all(b"    cmp     r8, rsi\n")?;
+    file.write_all(b"    je      .Lend\n")?;
+    file.write_all(b"    cmp     r8, rcx\n")?;
+    file.write_all(b"    je      .Lgreater\n")?;
+    file.write_all(b"    movzx   r9, byte [rdi+r8]\n")?;
+    file.write_all(b"    movzx   r10, byte [rdx+r8]\n")?;
+    file.write_all(b"    cmp     r9, r10\n")?;
+    file.write_all(b"    jb      .Lless\n")?;
+    file.write_all(b"    ja      .Lgreater\n")?;
+    file.write_all(b"    inc     r8\n")?;
+    file.write_all(b"    jmp     .Lnext\n")?;
+    file.write_all(b".Lend:\n")?;
+    file.write_all(b"    cmp     r8, rcx\n")?;
+    file.write_all(b"    je      .Lequal\n")?;
+    file.write_all(b".Lless:\n")?;
+    file.write_all(b"    mov     rax, -1\n")?;
+    file.write_all(b"    ret\n")?;
+    file.write_all(b".Lgreater:\n")?;
+    file.write_all(b"    mov     rax, 1\n")?;
+    file.write_all(b"    ret\n")?;
+    file.write_all(b".Lequal:\n")?;
+    file.write_all(b"    xor     rax, rax\n")?;
+    file.write_all(b"    ret\n")?;
+    file.write_all(b"__nmt_panic:\n")?;
+    file.write_all(b"    mov     rax, 1\n")?;
+    file.write_all(b"    mov     rdi, 2\n")?;
+    file.write_all(b"    syscall\n")?;
+    file.write_all(b"    mov     rax, 60\n")?;
+    file.write_all(b"    mov     rdi, 1\n")?;
+    file.write_all(b"    syscall\n")?;
 
     for instruct in &instruct_buf {
         file.write_all(instruct.as_bytes())?;
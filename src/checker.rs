@@ -0,0 +1,1572 @@
+/*  Copywrite Under MIT License by mahan farzaneh
+ *
+ *  Checker: Resolves names and checks the types of a parsed program
+ *  before it is handed to the compiler
+ *
+ * */
+use std::collections::HashMap;
+
+use crate::diagnostic::{Diagnostic, Span};
+use crate::parser::block::Block;
+use crate::parser::enums::EnumDef;
+use crate::parser::expr::{Expr, ExprKind, FunctionCall, Op, SizeOf};
+use crate::parser::function::Function;
+use crate::parser::parse_file;
+use crate::parser::program::{ProgramFile, ProgramItem};
+use crate::parser::stmt::{
+    Assgin, AssginOp, ElseBlock, ForIter, ForStmt, IFStmt, MatchStmt, Pattern, PrintFormat,
+    PrintStmt, Stmt, StmtKind, VariableDeclare, VariableType,
+};
+use crate::parser::structs::StructDef;
+
+/// Parses and checks a file, returning the resolved program and its warnings
+/// or every error followed by the warnings if any error is found
+pub fn check_file(path: String) -> Result<(ProgramFile, Vec<Diagnostic>), Vec<Diagnostic>> {
+    let mut program = parse_file(path)?;
+    let mut checker = Checker::new();
+    checker.check(&mut program);
+    if checker.diagnostics.is_empty() {
+        Ok((program, checker.warnings))
+    } else {
+        checker.diagnostics.extend(checker.warnings);
+        Err(checker.diagnostics)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FunctionSignature {
+    args: Vec<VariableType>,
+    ret_type: Option<VariableType>,
+}
+
+#[derive(Debug, Clone)]
+struct VariableInfo {
+    v_type: VariableType,
+    is_mut: bool,
+}
+
+pub struct Checker {
+    /// Errors found while checking
+    pub diagnostics: Vec<Diagnostic>,
+    /// Problems that do not stop the compilation
+    pub warnings: Vec<Diagnostic>,
+    functions: HashMap<String, FunctionSignature>,
+    structs: HashMap<String, StructDef>,
+    enums: HashMap<String, EnumDef>,
+    scopes: Vec<HashMap<String, VariableInfo>>,
+    ret_type: Option<VariableType>,
+    loop_depth: usize,
+}
+
+impl Checker {
+    pub fn new() -> Self {
+        Self {
+            diagnostics: Vec::new(),
+            warnings: Vec::new(),
+            functions: HashMap::new(),
+            structs: HashMap::new(),
+            enums: HashMap::new(),
+            scopes: vec![HashMap::new()],
+            ret_type: None,
+            loop_depth: 0,
+        }
+    }
+
+    /// Checks every item of the program
+    /// Signatures are collected first so functions can be called before their definition
+    pub fn check(&mut self, program: &mut ProgramFile) {
+        for item in program.items.iter() {
+            let res = match item {
+                ProgramItem::Struct(s) => self.insert_struct(s),
+                ProgramItem::Enum(e) => self.insert_enum(e),
+                _ => Ok(()),
+            };
+            if let Err(err) = res {
+                self.diagnostics.push(err);
+            }
+        }
+        // Fields are resolved after every struct is known
+        for item in program.items.iter_mut() {
+            if let ProgramItem::Struct(s) = item {
+                self.struct_fields(s);
+            }
+        }
+        for item in program.items.iter_mut() {
+            let res = match item {
+                ProgramItem::Func(f) => self.insert_function(f),
+                ProgramItem::StaticVar(v) => self.variable_declare(v),
+                ProgramItem::Import(path, idents, span) => {
+                    self.import(path, idents, span);
+                    Ok(())
+                }
+                ProgramItem::Struct(_) | ProgramItem::Enum(_) => Ok(()),
+            };
+            if let Err(err) = res {
+                self.diagnostics.push(err);
+            }
+        }
+        for item in program.items.iter_mut() {
+            if let ProgramItem::Func(f) = item {
+                self.function(f);
+            }
+        }
+    }
+
+    fn import(&mut self, path: &str, idents: &[String], span: &Span) {
+        let lib_path = format!("{path}.nmt");
+        let mut program = match parse_file(lib_path.clone()) {
+            Ok(program) => program,
+            Err(diags) => {
+                self.diagnostics.extend(diags);
+                return;
+            }
+        };
+        for ident in idents {
+            let function = program.items.iter_mut().find_map(|item| match item {
+                ProgramItem::Func(f) if &f.ident == ident => Some(f),
+                _ => None,
+            });
+            let res = match function {
+                Some(f) => self.insert_function(f),
+                None => Err(Diagnostic::error(format!(
+                    "Function ({ident}) is not defined in {lib_path}"
+                ))
+                .with_span(span.clone())),
+            };
+            if let Err(err) = res {
+                self.diagnostics.push(err);
+            }
+        }
+    }
+
+    fn insert_struct(&mut self, s: &StructDef) -> Result<(), Diagnostic> {
+        if self.structs.contains_key(&s.ident) || self.enums.contains_key(&s.ident) {
+            return Err(
+                Diagnostic::error(format!("Struct ({}) is already defined", s.ident))
+                    .with_span(s.span.clone()),
+            );
+        }
+        self.structs.insert(s.ident.clone(), s.clone());
+        Ok(())
+    }
+
+    fn insert_enum(&mut self, e: &EnumDef) -> Result<(), Diagnostic> {
+        if self.structs.contains_key(&e.ident) || self.enums.contains_key(&e.ident) {
+            return Err(
+                Diagnostic::error(format!("Enum ({}) is already defined", e.ident))
+                    .with_span(e.span.clone()),
+            );
+        }
+        let mut idents = Vec::<&String>::new();
+        for variant in e.variants.iter() {
+            if idents.contains(&&variant.ident) {
+                self.diagnostics.push(
+                    Diagnostic::error(format!(
+                        "Variant ({}) is already defined in enum ({})",
+                        variant.ident, e.ident
+                    ))
+                    .with_span(variant.span.clone()),
+                );
+            }
+            idents.push(&variant.ident);
+            let value = variant.value.as_ref().filter(|v| v.const_value().is_none());
+            if let Some(value) = value {
+                self.diagnostics.push(
+                    Diagnostic::error(format!(
+                        "Value of variant ({}) is not a compile time constant",
+                        variant.ident
+                    ))
+                    .with_span(value.span.clone()),
+                );
+            }
+        }
+        self.enums.insert(e.ident.clone(), e.clone());
+        Ok(())
+    }
+
+    fn struct_fields(&mut self, s: &mut StructDef) {
+        let mut idents = Vec::<String>::new();
+        for field in s.fields.iter_mut() {
+            if let Err(err) = self.resolve_type(&mut field.typedef, &field.span) {
+                self.diagnostics.push(err);
+            }
+            if idents.contains(&field.ident) {
+                self.diagnostics.push(
+                    Diagnostic::error(format!(
+                        "Field ({}) is already defined in struct ({})",
+                        field.ident, s.ident
+                    ))
+                    .with_span(field.span.clone()),
+                );
+            }
+            idents.push(field.ident.clone());
+        }
+        self.structs.insert(s.ident.clone(), s.clone());
+        let contains_itself = s
+            .fields
+            .iter()
+            .any(|field| self.contains_struct(&field.typedef, &s.ident, &mut Vec::new()));
+        if contains_itself {
+            self.diagnostics.push(
+                Diagnostic::error(format!("Struct ({}) contains itself", s.ident))
+                    .with_span(s.span.clone())
+                    .with_note("Use a ptr to the struct instead"),
+            );
+        }
+    }
+
+    /// Returns true if a value of the type stores the struct in place
+    fn contains_struct(&self, t: &VariableType, target: &str, visited: &mut Vec<String>) -> bool {
+        match t {
+            VariableType::Array(t, _) => self.contains_struct(t, target, visited),
+            VariableType::Struct(ident) if ident == target => true,
+            VariableType::Struct(ident) if !visited.contains(ident) => {
+                visited.push(ident.clone());
+                self.structs.get(ident).is_some_and(|s| {
+                    s.fields
+                        .iter()
+                        .any(|field| self.contains_struct(&field.typedef, target, visited))
+                })
+            }
+            _ => false,
+        }
+    }
+
+    /// Resolves custom types that name a struct or an enum
+    /// Unknown names are replaced by @int so their uses are not reported again
+    fn resolve_type(&self, t: &mut VariableType, span: &Span) -> Result<(), Diagnostic> {
+        match t {
+            VariableType::Custom(ident) if self.structs.contains_key(ident) => {
+                *t = VariableType::Struct(ident.clone());
+            }
+            VariableType::Custom(ident) if self.enums.contains_key(ident) => {
+                *t = VariableType::Enum(ident.clone());
+            }
+            VariableType::Custom(ident) => {
+                let err = Diagnostic::error(format!("Type ({ident}) is not defined"))
+                    .with_span(span.clone());
+                *t = VariableType::Int;
+                return Err(err);
+            }
+            VariableType::Array(t, _) | VariableType::Pointer(Some(t)) | VariableType::Slice(t) => {
+                self.resolve_type(t, span)?
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn insert_function(&mut self, f: &mut Function) -> Result<(), Diagnostic> {
+        if self.functions.contains_key(&f.ident) {
+            return Err(
+                Diagnostic::error(format!("Function ({}) is already defined", f.ident))
+                    .with_span(f.span.clone()),
+            );
+        }
+        if f.ident.starts_with("__nmt_") {
+            return Err(Diagnostic::error(format!(
+                "Function ({}) uses the prefix __nmt_ that is reserved for the runtime",
+                f.ident
+            ))
+            .with_span(f.span.clone()));
+        }
+        for arg in f.args.iter_mut() {
+            if let Err(err) = self.resolve_type(&mut arg.typedef, &arg.span) {
+                self.diagnostics.push(err);
+            }
+            if let VariableType::Struct(_) = arg.typedef {
+                self.diagnostics.push(
+                    Diagnostic::error(format!(
+                        "Structs can not be passed by value, use a ptr for argument ({})",
+                        arg.ident
+                    ))
+                    .with_span(arg.span.clone()),
+                );
+            }
+            if let VariableType::Array(_, _) = arg.typedef {
+                self.diagnostics.push(
+                    Diagnostic::error(format!(
+                        "Arrays can not be passed by value, use a slice for argument ({})",
+                        arg.ident
+                    ))
+                    .with_span(arg.span.clone()),
+                );
+            }
+        }
+        if let Some(ret_type) = f.ret_type.as_mut() {
+            if let Err(err) = self.resolve_type(ret_type, &f.span) {
+                self.diagnostics.push(err);
+            }
+            if f.ident == "main" && ret_type.int_size().is_none() {
+                self.diagnostics.push(
+                    Diagnostic::error(format!(
+                        "main can only return an integer exit status found {ret_type}"
+                    ))
+                    .with_span(f.span.clone()),
+                );
+            }
+            if let VariableType::Struct(_) = ret_type {
+                self.diagnostics.push(
+                    Diagnostic::error("Structs can not be returned by value")
+                        .with_span(f.span.clone())
+                        .with_note("Return a ptr to the struct instead"),
+                );
+            }
+        }
+        let signature = FunctionSignature {
+            args: f.args.iter().map(|arg| arg.typedef.clone()).collect(),
+            ret_type: f.ret_type.clone(),
+        };
+        self.functions.insert(f.ident.clone(), signature);
+        Ok(())
+    }
+
+    fn function(&mut self, f: &mut Function) {
+        self.ret_type = f.ret_type.clone();
+        self.scopes.push(HashMap::new());
+        for arg in &f.args {
+            self.declare(&arg.ident, arg.typedef.clone(), false);
+        }
+        self.block(&mut f.block);
+        self.scopes.pop();
+    }
+
+    fn block(&mut self, block: &mut Block) {
+        self.scopes.push(HashMap::new());
+        for stmt in block.stmts.iter_mut() {
+            if let Err(err) = self.stmt(stmt) {
+                self.diagnostics.push(err);
+            }
+        }
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, ident: &str, v_type: VariableType, is_mut: bool) {
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert(ident.to_string(), VariableInfo { v_type, is_mut });
+    }
+
+    fn variable(&self, ident: &String, span: &Span) -> Result<VariableInfo, Diagnostic> {
+        for scope in self.scopes.iter().rev() {
+            if let Some(info) = scope.get(ident) {
+                return Ok(info.clone());
+            }
+        }
+        Err(
+            Diagnostic::error(format!("Trying to access an Undifined variable ({ident})"))
+                .with_span(span.clone()),
+        )
+    }
+
+    fn stmt(&mut self, stmt: &mut Stmt) -> Result<(), Diagnostic> {
+        match &mut stmt.kind {
+            StmtKind::VariableDecl(v) => self.variable_declare(v)?,
+            StmtKind::Assgin(a) => self.assgin(a)?,
+            StmtKind::Print(p) => self.print(p)?,
+            StmtKind::Panic(message) => self.panic_message(message)?,
+            StmtKind::Assert(a) => {
+                if let Err(err) = self.condition(&mut a.condition) {
+                    self.diagnostics.push(err);
+                }
+                if let Some(message) = a.message.as_mut() {
+                    self.panic_message(message)?;
+                }
+            }
+            StmtKind::While(w) => {
+                if let Err(err) = self.condition(&mut w.condition) {
+                    self.diagnostics.push(err);
+                }
+                self.loop_depth += 1;
+                self.block(&mut w.block);
+                self.loop_depth -= 1;
+            }
+            StmtKind::If(ifs) => self.if_stmt(ifs),
+            StmtKind::Match(m) => self.match_stmt(m, &stmt.span)?,
+            StmtKind::For(f) => self.for_stmt(f)?,
+            StmtKind::Return(e) => match (self.ret_type.clone(), e) {
+                (Some(expected), Some(e)) => {
+                    let found = self.expr(e)?;
+                    self.expect_type(&expected, &found, &e.span)?;
+                }
+                (Some(expected), None) => {
+                    return Err(Diagnostic::error(format!(
+                        "Expected a return value of type {expected}"
+                    ))
+                    .with_span(stmt.span.clone()));
+                }
+                (None, Some(e)) => {
+                    return Err(Diagnostic::error(
+                        "Returning a value from a function with no return type",
+                    )
+                    .with_span(e.span.clone())
+                    .with_note("Add a return type to the function e.g: func f() @int"));
+                }
+                (None, None) => {}
+            },
+            StmtKind::Expr(e) => match &mut e.kind {
+                ExprKind::FunctionCall(fc) => {
+                    self.function_call(fc, &e.span)?;
+                }
+                _ => {
+                    self.expr(e)?;
+                }
+            },
+            StmtKind::Break | StmtKind::Continue if self.loop_depth == 0 => {
+                let keyword = match stmt.kind {
+                    StmtKind::Break => "break",
+                    _ => "continue",
+                };
+                return Err(Diagnostic::error(format!("{keyword} outside of a loop"))
+                    .with_span(stmt.span.clone()));
+            }
+            StmtKind::InlineAsm(_) | StmtKind::Break | StmtKind::Continue => {}
+        }
+        Ok(())
+    }
+
+    fn print(&mut self, p: &mut PrintStmt) -> Result<(), Diagnostic> {
+        let t = self.expr(&mut p.expr)?;
+        if let VariableType::Array(_, _) | VariableType::Slice(_) | VariableType::Struct(_) = t {
+            return Err(
+                Diagnostic::error(format!("Can not print a value of type {t}"))
+                    .with_span(p.expr.span.clone())
+                    .with_note("Print the items one by one"),
+            );
+        }
+        match p.format {
+            Some(_) if t == VariableType::String => {
+                Err(Diagnostic::error("Strings can not be formatted")
+                    .with_span(p.expr.span.clone()))
+            }
+            Some(format) if t.is_float() && format != PrintFormat::Dec => Err(Diagnostic::error(
+                format!("Floats can only be printed as dec found {t}"),
+            )
+            .with_span(p.expr.span.clone())),
+            Some(PrintFormat::Char) if !t.is_numeric() => Err(Diagnostic::error(format!(
+                "Can not print a value of type {t} as char"
+            ))
+            .with_span(p.expr.span.clone())),
+            _ => Ok(()),
+        }
+    }
+
+    fn panic_message(&mut self, message: &mut Expr) -> Result<(), Diagnostic> {
+        let t = self.expr(message)?;
+        if t != VariableType::String {
+            return Err(
+                Diagnostic::error(format!("Expected a message of type @str found {t}"))
+                    .with_span(message.span.clone()),
+            );
+        }
+        Ok(())
+    }
+
+    fn if_stmt(&mut self, ifs: &mut IFStmt) {
+        if let Err(err) = self.condition(&mut ifs.condition) {
+            self.diagnostics.push(err);
+        }
+        self.block(&mut ifs.then_block);
+        match ifs.else_block.as_mut() {
+            ElseBlock::Elif(iff) => self.if_stmt(iff),
+            ElseBlock::Else(b) => self.block(b),
+            ElseBlock::None => {}
+        }
+    }
+
+    fn for_stmt(&mut self, f: &mut ForStmt) -> Result<(), Diagnostic> {
+        let v_type = match &mut f.iter {
+            ForIter::Range {
+                start, end, step, ..
+            } => {
+                let first = self.expr(start)?;
+                let last = self.expr(end)?;
+                let v_type = self.binary_type(&Op::Plus, &first, &last, &end.span)?;
+                if !v_type.is_numeric() {
+                    return Err(Diagnostic::error(format!(
+                        "Can not iterate over a range of type {v_type}"
+                    ))
+                    .with_span(start.span.clone()));
+                }
+                if let Some(step) = step {
+                    let t = self.expr(step)?;
+                    self.expect_type(&VariableType::Int, &t, &step.span)?;
+                    if step.const_value() == Some(0) {
+                        return Err(Diagnostic::error("Step of a for loop can not be zero")
+                            .with_span(step.span.clone()));
+                    }
+                }
+                v_type
+            }
+            ForIter::Array(array) => match self.expr(array)? {
+                VariableType::String => VariableType::Char,
+                VariableType::Array(t, _) | VariableType::Slice(t) => match *t {
+                    VariableType::Array(_, _)
+                    | VariableType::Struct(_)
+                    | VariableType::String
+                    | VariableType::Slice(_) => {
+                        return Err(Diagnostic::error(format!(
+                            "Can not iterate over items of type {t} by value"
+                        ))
+                        .with_span(array.span.clone())
+                        .with_note("Iterate over the indices instead e.g: for i in 0..n"));
+                    }
+                    t => t,
+                },
+                t => {
+                    return Err(Diagnostic::error(format!(
+                        "Can not iterate over a value of type {t}"
+                    ))
+                    .with_span(array.span.clone()));
+                }
+            },
+        };
+        f.v_type = Some(v_type.clone());
+        // The loop variable is only visible in the body and can not be modified
+        self.scopes.push(HashMap::new());
+        self.declare(&f.ident, v_type, false);
+        self.loop_depth += 1;
+        self.block(&mut f.block);
+        self.loop_depth -= 1;
+        self.scopes.pop();
+        Ok(())
+    }
+
+    fn match_stmt(&mut self, m: &mut MatchStmt, span: &Span) -> Result<(), Diagnostic> {
+        let t = self.expr(&mut m.expr)?;
+        if !t.is_numeric() && !matches!(t, VariableType::Enum(_)) {
+            return Err(
+                Diagnostic::error(format!("Can not match a value of type {t}"))
+                    .with_span(m.expr.span.clone())
+                    .with_note("Only integers, chars and enums can be matched"),
+            );
+        }
+        let mut has_default = false;
+        // (first value, last value) of every pattern
+        let mut covered = Vec::<(i64, i64)>::new();
+        for arm in m.arms.iter_mut() {
+            for pattern in arm.patterns.iter_mut() {
+                let res = match pattern {
+                    Pattern::Default if has_default => {
+                        Err(Diagnostic::error("Match has more than one default arm")
+                            .with_span(arm.span.clone()))
+                    }
+                    Pattern::Default => {
+                        has_default = true;
+                        Ok(())
+                    }
+                    Pattern::Value(value) => self
+                        .pattern_value(value, &t)
+                        .map(|value| covered.push((value, value))),
+                    Pattern::Range {
+                        start,
+                        end,
+                        inclusive,
+                    } => self.pattern_value(start, &t).and_then(|first| {
+                        let last = self.pattern_value(end, &t)?;
+                        if first > last || (first == last && !*inclusive) {
+                            return Err(Diagnostic::error("Range pattern is empty")
+                                .with_span(arm.span.clone()));
+                        }
+                        covered.push((first, last - !*inclusive as i64));
+                        Ok(())
+                    }),
+                };
+                if let Err(err) = res {
+                    self.diagnostics.push(err);
+                }
+            }
+            self.block(&mut arm.block);
+        }
+        if let (VariableType::Enum(ident), false) = (&t, has_default) {
+            let e = &self.enums[ident];
+            let missing: Vec<&str> = e
+                .variants
+                .iter()
+                .zip(e.values())
+                .filter(|(_, value)| !covered.iter().any(|c| c.0 <= *value && *value <= c.1))
+                .map(|(variant, _)| variant.ident.as_str())
+                .collect();
+            if !missing.is_empty() {
+                self.warnings.push(
+                    Diagnostic::warning(format!(
+                        "Match over enum ({ident}) does not cover the variant(s) {}",
+                        missing.join(", ")
+                    ))
+                    .with_span(span.clone()),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the value of a match pattern that has to be known at compile time
+    fn pattern_value(&mut self, value: &mut Expr, t: &VariableType) -> Result<i64, Diagnostic> {
+        let found = self.expr(value)?;
+        self.expect_type(t, &found, &value.span)?;
+        value.const_value().ok_or_else(|| {
+            Diagnostic::error("Match pattern is not a compile time constant")
+                .with_span(value.span.clone())
+        })
+    }
+
+    fn condition(&mut self, condition: &mut Expr) -> Result<(), Diagnostic> {
+        let t = self.expr(condition)?;
+        if !t.is_numeric() && !matches!(t, VariableType::Pointer(_)) {
+            return Err(Diagnostic::error(format!(
+                "Expected a condition found a value of type {t}"
+            ))
+            .with_span(condition.span.clone()));
+        }
+        Ok(())
+    }
+
+    fn variable_declare(&mut self, v: &mut VariableDeclare) -> Result<(), Diagnostic> {
+        if let Some(t) = v.v_type.as_mut() {
+            if let Err(err) = self.resolve_type(t, &v.span) {
+                self.diagnostics.push(err);
+            }
+        }
+        let res = match (&v.v_type, &mut v.init_value) {
+            (Some(expected), Some(init)) => self.initializer(Some(expected), init).map(|_| ()),
+            (None, Some(init)) => self.initializer(None, init).map(|found| {
+                v.v_type = Some(found);
+            }),
+            (Some(_), None) => Ok(()),
+            (None, None) => Err(Diagnostic::error(format!(
+                "Can not infer the type of variable ({})",
+                v.ident
+            ))
+            .with_span(v.span.clone())
+            .with_note(format!("Add a type to it e.g: var {} @int;", v.ident))),
+        };
+        // Globals and statics live in the data section
+        let is_static = v.is_static || self.scopes.len() == 1;
+        // A string literal is stored in place in a char array or as a str
+        let holds_string = matches!(
+            v.v_type,
+            Some(VariableType::Array(_, _) | VariableType::String)
+        );
+        let init = v.init_value.as_ref().filter(|init| {
+            is_static
+                && (!init.is_const() || (!holds_string && matches!(init.kind, ExprKind::String(_))))
+        });
+        if let Some(init) = init {
+            self.diagnostics.push(
+                Diagnostic::error(format!(
+                    "Initial value of static variable ({}) is not a compile time constant",
+                    v.ident
+                ))
+                .with_span(init.span.clone()),
+            );
+        }
+        if let (Some(VariableType::Struct(_)), Some(init)) = (&v.v_type, &v.init_value) {
+            self.diagnostics.push(
+                Diagnostic::error(format!(
+                    "Struct variable ({}) can not be initialized as a whole",
+                    v.ident
+                ))
+                .with_span(init.span.clone())
+                .with_note("Assign the fields one by one"),
+            );
+        }
+        // Declared even on errors so later uses do not report it again
+        let v_type = v.v_type.clone().unwrap_or(VariableType::Int);
+        self.declare(&v.ident, v_type, v.mutable);
+        res
+    }
+
+    /// Checks the initial value of a variable and returns its type
+    /// Array literals and strings fill arrays in place so they are only valid here
+    fn initializer(
+        &mut self,
+        expected: Option<&VariableType>,
+        init: &mut Expr,
+    ) -> Result<VariableType, Diagnostic> {
+        let found = match (expected, &mut init.kind) {
+            (Some(VariableType::Array(t, len)), ExprKind::ArrayLiteral(items)) => {
+                if items.len() > *len {
+                    return Err(Diagnostic::error(format!(
+                        "Array literal has {} elements but the array holds {len}",
+                        items.len()
+                    ))
+                    .with_span(init.span.clone()));
+                }
+                for item in items.iter_mut() {
+                    self.initializer(Some(t), item)?;
+                }
+                VariableType::Array(t.clone(), *len)
+            }
+            (Some(VariableType::Array(t, len)), ExprKind::String(str))
+                if **t == VariableType::Char =>
+            {
+                if str.len() > *len {
+                    return Err(Diagnostic::error(format!(
+                        "String literal has {} bytes but the array holds {len}",
+                        str.len()
+                    ))
+                    .with_span(init.span.clone()));
+                }
+                VariableType::Array(t.clone(), *len)
+            }
+            (Some(t), ExprKind::ArrayLiteral(_)) => {
+                return Err(Diagnostic::error(format!(
+                    "Can not initialize a value of type {t} with an array literal"
+                ))
+                .with_span(init.span.clone()));
+            }
+            (None, ExprKind::ArrayLiteral(items)) => {
+                let Some((first, rest)) = items.split_first_mut() else {
+                    return Err(Diagnostic::error(
+                        "Can not infer the type of an empty array literal",
+                    )
+                    .with_span(init.span.clone())
+                    .with_note("Add a type to the variable e.g: @[int,4]"));
+                };
+                // Mixed numbers are stored as their common type
+                let mut t = self.initializer(None, first)?;
+                for item in rest.iter_mut() {
+                    let found = self.initializer(None, item)?;
+                    self.expect_type(&t, &found, &item.span)?;
+                    if t.is_arithmetic() && found.is_arithmetic() {
+                        t = t.arith_type(&found);
+                    }
+                }
+                VariableType::Array(Box::new(t), items.len())
+            }
+            _ => {
+                let found = self.expr(init)?;
+                if let VariableType::Array(_, _) = found {
+                    return Err(
+                        Diagnostic::error(format!("Can not copy a value of type {found}"))
+                            .with_span(init.span.clone())
+                            .with_note("Copy it element by element"),
+                    );
+                }
+                if let Some(expected) = expected {
+                    self.expect_type(expected, &found, &init.span)?;
+                }
+                return Ok(found);
+            }
+        };
+        if let VariableType::Array(t, _) = &found {
+            if let VariableType::Struct(_) = t.as_ref() {
+                return Err(Diagnostic::error(format!(
+                    "Array of type {found} can not be initialized with a literal"
+                ))
+                .with_span(init.span.clone())
+                .with_note("Assign the fields one by one"));
+            }
+        }
+        init.v_type = Some(found.clone());
+        Ok(found)
+    }
+
+    /// Returns the variable that is modified by writing to an lvalue
+    /// Writing through a pointer does not modify a variable
+    fn written_variable(lvalue: &Expr) -> Option<&String> {
+        match &lvalue.kind {
+            ExprKind::Variable(ident) => Some(ident),
+            ExprKind::ArrayIndex(ai) => match ai.base.v_type {
+                Some(VariableType::Array(_, _)) => Self::written_variable(&ai.base),
+                _ => None,
+            },
+            ExprKind::Field(fa) => match fa.base.v_type {
+                Some(VariableType::Struct(_)) => Self::written_variable(&fa.base),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn assgin(&mut self, assign: &mut Assgin) -> Result<(), Diagnostic> {
+        if !assign.left.is_lvalue() {
+            return Err(
+                Diagnostic::error("Expected a Variable type expression found Value")
+                    .with_span(assign.left.span.clone()),
+            );
+        }
+        let left = self.expr(&mut assign.left)?;
+        if let Some(ident) = Self::written_variable(&assign.left) {
+            let info = self.variable(ident, &assign.left.span)?;
+            if !info.is_mut {
+                return Err(
+                    Diagnostic::error(format!("Variable ({ident}) is not mutable"))
+                        .with_span(assign.left.span.clone())
+                        .with_note("Did you forgot to define it with '=' insted of ':=' ?"),
+                );
+            }
+        }
+        if let VariableType::Array(_, _) = left {
+            return Err(Diagnostic::error("Can not assign to an array as a whole")
+                .with_span(assign.left.span.clone()));
+        }
+        if let VariableType::Struct(_) = left {
+            return Err(Diagnostic::error("Can not assign to a struct as a whole")
+                .with_span(assign.left.span.clone())
+                .with_note("Assign the fields one by one"));
+        }
+        let right = self.expr(&mut assign.right)?;
+        let op = match assign.op {
+            AssginOp::Eq => return self.expect_type(&left, &right, &assign.right.span),
+            AssginOp::PlusEq => Op::Plus,
+            AssginOp::SubEq => Op::Sub,
+            AssginOp::MultiEq => Op::Multi,
+            AssginOp::DevideEq => Op::Devide,
+            AssginOp::ModEq => Op::Mod,
+        };
+        let result = self.binary_type(&op, &left, &right, &assign.right.span)?;
+        self.expect_type(&left, &result, &assign.right.span)
+    }
+
+    /// Returns the called function return type
+    fn function_call(
+        &mut self,
+        fc: &mut FunctionCall,
+        span: &Span,
+    ) -> Result<Option<VariableType>, Diagnostic> {
+        let Some(signature) = self.functions.get(&fc.ident).cloned() else {
+            return Err(Diagnostic::error(format!(
+                "Function {} is not avaliable in this scope.",
+                &fc.ident
+            ))
+            .with_span(span.clone())
+            .with_note("Make sure you are calling the correct function"));
+        };
+        if fc.args.len() != signature.args.len() {
+            return Err(Diagnostic::error(format!(
+                "Function ({}) takes {} argument(s) but {} were given",
+                fc.ident,
+                signature.args.len(),
+                fc.args.len()
+            ))
+            .with_span(span.clone()));
+        }
+        for (arg, expected) in fc.args.iter_mut().zip(signature.args.iter()) {
+            let found = self.expr(arg)?;
+            self.expect_type(expected, &found, &arg.span)?;
+        }
+        Ok(signature.ret_type)
+    }
+
+    /// Returns the type of the expression and stores it in the expression
+    fn expr(&mut self, expr: &mut Expr) -> Result<VariableType, Diagnostic> {
+        let v_type = self.expr_kind(&mut expr.kind, &expr.span)?;
+        expr.v_type = Some(v_type.clone());
+        Ok(v_type)
+    }
+
+    fn expr_kind(&mut self, kind: &mut ExprKind, span: &Span) -> Result<VariableType, Diagnostic> {
+        match kind {
+            ExprKind::Int(_) => Ok(VariableType::Int),
+            ExprKind::Float(_) => Ok(VariableType::F64),
+            ExprKind::Char(_) => Ok(VariableType::Char),
+            ExprKind::String(_) => Ok(VariableType::String),
+            ExprKind::ArrayLiteral(_) => Err(Diagnostic::error(
+                "Array literals can only initialize array variables",
+            )
+            .with_span(span.clone())),
+            ExprKind::Variable(ident) => Ok(self.variable(ident, span)?.v_type),
+            ExprKind::Ptr(e) => {
+                let t = self.expr(e)?;
+                if !e.is_lvalue() {
+                    return Err(Diagnostic::error("Can not take the address of a value")
+                        .with_span(span.clone())
+                        .with_note("Store the value in a variable first"));
+                }
+                Ok(VariableType::Pointer(Some(Box::new(t))))
+            }
+            ExprKind::Deref(e) => match self.expr(e)? {
+                VariableType::Pointer(Some(t)) => Ok(*t),
+                VariableType::Pointer(None) => {
+                    Err(Diagnostic::error("Can not dereference an untyped pointer")
+                        .with_span(span.clone())
+                        .with_note("Give the pointer a type e.g: @ptr int"))
+                }
+                t => Err(
+                    Diagnostic::error(format!("Can not dereference a value of type {t}"))
+                        .with_span(span.clone()),
+                ),
+            },
+            ExprKind::Unary(u) => {
+                let t = self.expr(&mut u.right)?;
+                // Floats can only be negated
+                let valid = if t.is_float() {
+                    u.op != Op::Not
+                } else {
+                    t.is_numeric()
+                };
+                if !valid {
+                    return Err(Diagnostic::error(format!(
+                        "Can not apply ({}) to a value of type {t}",
+                        u.op
+                    ))
+                    .with_span(span.clone()));
+                }
+                Ok(t)
+            }
+            ExprKind::Binary(b) => {
+                let left = self.expr(&mut b.left)?;
+                let right = self.expr(&mut b.right)?;
+                self.binary_type(&b.op, &left, &right, span)
+            }
+            ExprKind::Compare(c) => {
+                let left = self.expr(&mut c.left)?;
+                let right = self.expr(&mut c.right)?;
+                let comparable = match (&left, &right) {
+                    (VariableType::Pointer(_), VariableType::Pointer(_)) => true,
+                    // Strings are compared by their content
+                    (VariableType::String, VariableType::String) => true,
+                    (VariableType::Enum(l), VariableType::Enum(r)) => l == r,
+                    (l, r) => l.is_arithmetic() && r.is_arithmetic(),
+                };
+                if !comparable {
+                    return Err(Diagnostic::error(format!(
+                        "Can not compare a value of type {left} with {right}"
+                    ))
+                    .with_span(span.clone()));
+                }
+                Ok(VariableType::Bool)
+            }
+            ExprKind::Logical(l) => {
+                self.condition(&mut l.left)?;
+                self.condition(&mut l.right)?;
+                Ok(VariableType::Bool)
+            }
+            ExprKind::Not(e) => {
+                self.condition(e)?;
+                Ok(VariableType::Bool)
+            }
+            ExprKind::Cast(c) => {
+                self.resolve_type(&mut c.target, span)?;
+                let from = self.expr(&mut c.expr)?;
+                let to = &c.target;
+                // Integers convert to anything scalar and floats only to numbers
+                let is_int =
+                    |t: &VariableType| t.is_numeric() || matches!(t, VariableType::Enum(_));
+                let is_address =
+                    |t: &VariableType| matches!(t, VariableType::Pointer(_) | VariableType::String);
+                let valid = match (&from, to) {
+                    (f, t) if f.is_float() || t.is_float() => {
+                        f.is_arithmetic() && t.is_arithmetic()
+                    }
+                    // A cast keeps the address of a str but can not make its length
+                    (f, VariableType::String) => *f == VariableType::String,
+                    (f, t) => (is_int(f) || is_address(f)) && (is_int(t) || is_address(t)),
+                };
+                if !valid {
+                    let err =
+                        Diagnostic::error(format!("Can not cast a value of type {from} to {to}"))
+                            .with_span(span.clone());
+                    return Err(match to {
+                        VariableType::String => {
+                            err.with_note("Slice a pointer to chars instead e.g: p[..n]")
+                        }
+                        _ => err,
+                    });
+                }
+                Ok(to.clone())
+            }
+            ExprKind::SizeOf(s) => {
+                match s {
+                    SizeOf::Type(t) => self.resolve_type(t, span)?,
+                    SizeOf::Expr(e) => {
+                        self.expr(e)?;
+                    }
+                }
+                Ok(VariableType::UInt)
+            }
+            ExprKind::FunctionCall(fc) => match self.function_call(fc, span)? {
+                Some(t) => Ok(t),
+                None => Err(Diagnostic::error(format!(
+                    "Function ({}) does not return a value",
+                    fc.ident
+                ))
+                .with_span(span.clone())),
+            },
+            ExprKind::ArrayIndex(ai) => {
+                let base = self.expr(&mut ai.base)?;
+                let index = self.expr(&mut ai.indexer)?;
+                if !index.is_numeric() {
+                    return Err(Diagnostic::error(format!(
+                        "Array index must be an integer found {index}"
+                    ))
+                    .with_span(ai.indexer.span.clone()));
+                }
+                // Constant indices are checked even when runtime checks are off
+                if let (VariableType::Array(_, len), Some(i)) = (&base, ai.indexer.const_value()) {
+                    if i < 0 || i as usize >= *len {
+                        return Err(Diagnostic::error(format!(
+                            "Index {i} is out of bounds for an array of length {len}"
+                        ))
+                        .with_span(ai.indexer.span.clone()));
+                    }
+                }
+                match base {
+                    VariableType::Array(t, _)
+                    | VariableType::Pointer(Some(t))
+                    | VariableType::Slice(t) => Ok(*t),
+                    VariableType::String => Ok(VariableType::Char),
+                    t => Err(
+                        Diagnostic::error(format!("Can not index a value of type {t}"))
+                            .with_span(span.clone()),
+                    ),
+                }
+            }
+            ExprKind::Slice(sl) => {
+                let base = self.expr(&mut sl.base)?;
+                for index in [&mut sl.start, &mut sl.end].into_iter().flatten() {
+                    let t = self.expr(index)?;
+                    if !t.is_numeric() {
+                        return Err(Diagnostic::error(format!(
+                            "Slice index must be an integer found {t}"
+                        ))
+                        .with_span(index.span.clone()));
+                    }
+                }
+                // Slices of chars are strings
+                let slice = |t: Box<VariableType>| match *t {
+                    VariableType::Char => VariableType::String,
+                    t => VariableType::Slice(Box::new(t)),
+                };
+                match base {
+                    VariableType::String => Ok(VariableType::String),
+                    VariableType::Array(t, _) | VariableType::Slice(t) => Ok(slice(t)),
+                    VariableType::Pointer(Some(t)) => {
+                        if sl.end.is_none() {
+                            return Err(Diagnostic::error("Slice of a pointer needs an end index")
+                                .with_span(span.clone()));
+                        }
+                        Ok(slice(t))
+                    }
+                    t => Err(
+                        Diagnostic::error(format!("Can not slice a value of type {t}"))
+                            .with_span(span.clone()),
+                    ),
+                }
+            }
+            ExprKind::Len(e) => match self.expr(e)? {
+                VariableType::String | VariableType::Slice(_) | VariableType::Array(_, _) => {
+                    Ok(VariableType::Int)
+                }
+                t => Err(Diagnostic::error(format!(
+                    "Can not take the length of a value of type {t}"
+                ))
+                .with_span(span.clone())),
+            },
+            ExprKind::EnumVariant(ev) => {
+                let Some(e) = self.enums.get(&ev.ident) else {
+                    return Err(
+                        Diagnostic::error(format!("Enum ({}) is not defined", ev.ident))
+                            .with_span(span.clone()),
+                    );
+                };
+                let Some(index) = e.variants.iter().position(|v| v.ident == ev.variant) else {
+                    return Err(Diagnostic::error(format!(
+                        "Enum ({}) has no variant ({})",
+                        ev.ident, ev.variant
+                    ))
+                    .with_span(span.clone()));
+                };
+                ev.value = Some(e.values()[index]);
+                Ok(VariableType::Enum(ev.ident.clone()))
+            }
+            ExprKind::Field(fa) => {
+                // Fields of a struct pointer are accessed through the pointer
+                let base = match self.expr(&mut fa.base)? {
+                    VariableType::Pointer(Some(t)) => *t,
+                    t => t,
+                };
+                match base {
+                    VariableType::Struct(ident) => self.field_type(&ident, &fa.field, span),
+                    t => Err(Diagnostic::error(format!(
+                        "Can not access field ({}) of a value of type {t}",
+                        fa.field
+                    ))
+                    .with_span(span.clone())),
+                }
+            }
+        }
+    }
+
+    fn field_type(
+        &self,
+        ident: &str,
+        field: &str,
+        span: &Span,
+    ) -> Result<VariableType, Diagnostic> {
+        self.structs[ident]
+            .fields
+            .iter()
+            .find(|f| f.ident == field)
+            .map(|f| f.typedef.clone())
+            .ok_or_else(|| {
+                Diagnostic::error(format!("Struct ({ident}) has no field ({field})"))
+                    .with_span(span.clone())
+            })
+    }
+
+    fn binary_type(
+        &self,
+        op: &Op,
+        left: &VariableType,
+        right: &VariableType,
+        span: &Span,
+    ) -> Result<VariableType, Diagnostic> {
+        match (left, right) {
+            (l, r) if l.is_numeric() && r.is_numeric() => Ok(l.arith_type(r)),
+            // Integers are converted to floats
+            (l, r)
+                if (l.is_float() || r.is_float())
+                    && l.is_arithmetic()
+                    && r.is_arithmetic()
+                    && matches!(op, Op::Plus | Op::Sub | Op::Multi | Op::Devide) =>
+            {
+                Ok(l.arith_type(r))
+            }
+            // Pointer arithmetic is scaled by the size of the pointee
+            (VariableType::Pointer(_), r) if r.is_numeric() && matches!(op, Op::Plus | Op::Sub) => {
+                Ok(left.clone())
+            }
+            (l, VariableType::Pointer(_)) if l.is_numeric() && *op == Op::Plus => Ok(right.clone()),
+            (VariableType::Pointer(_), VariableType::Pointer(_))
+                if left == right && *op == Op::Sub =>
+            {
+                Ok(VariableType::Int)
+            }
+            _ => Err(Diagnostic::error(format!(
+                "Can not apply ({op}) to values of type {left} and {right}"
+            ))
+            .with_span(span.clone())),
+        }
+    }
+
+    fn expect_type(
+        &self,
+        expected: &VariableType,
+        found: &VariableType,
+        span: &Span,
+    ) -> Result<(), Diagnostic> {
+        let compatible = match (expected, found) {
+            // Untyped pointers convert to and from any pointer
+            (VariableType::Pointer(None), VariableType::Pointer(_) | VariableType::String) => true,
+            (VariableType::Pointer(_), VariableType::Pointer(None)) => true,
+            (VariableType::Pointer(Some(t)), VariableType::String) => **t == VariableType::Char,
+            // Integers and floats convert to each other
+            (e, f) => e == f || (e.is_arithmetic() && f.is_arithmetic()),
+        };
+        if compatible {
+            Ok(())
+        } else {
+            Err(Diagnostic::error(format!(
+                "Mismatched types: expected {expected} found {found}"
+            ))
+            .with_span(span.clone()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod checker_tests {
+    use super::Checker;
+    use crate::lexer::Lexer;
+    use crate::parser::program;
+    use crate::parser::program::ProgramItem;
+    use crate::parser::stmt::{StmtKind, VariableType};
+
+    fn check(source: &str) -> (Checker, crate::parser::program::ProgramFile) {
+        let mut lexer = Lexer::new("test.nmt".to_string(), source.to_string());
+        let mut program = program(&mut lexer);
+        assert!(lexer.diagnostics.is_empty());
+        let mut checker = Checker::new();
+        checker.check(&mut program);
+        (checker, program)
+    }
+
+    #[test]
+    fn report_every_type_error() {
+        let source = "func f(x @int) @int {\n    return \"no\";\n}\nfunc main() {\n    f(1, 2);\n    f(\"s\");\n    var a := 1;\n    a = 2;\n    print a[0];\n    print b;\n}";
+        let (checker, _) = check(source);
+        let lines: Vec<usize> = checker
+            .diagnostics
+            .iter()
+            .map(|diag| diag.span.as_ref().unwrap().line)
+            .collect();
+        assert_eq!(lines, vec![2, 5, 6, 8, 9, 10]);
+    }
+
+    #[test]
+    fn infer_untyped_variables() {
+        let (checker, program) = check("func main() {\n    var c = 'c';\n    var p = ptr c;\n}");
+        assert!(checker.diagnostics.is_empty());
+        let ProgramItem::Func(f) = &program.items[0] else {
+            panic!("Expected a function");
+        };
+        let types: Vec<Option<VariableType>> = f
+            .block
+            .stmts
+            .iter()
+            .map(|stmt| match &stmt.kind {
+                StmtKind::VariableDecl(v) => v.v_type.clone(),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                Some(VariableType::Char),
+                Some(VariableType::Pointer(Some(Box::new(VariableType::Char))))
+            ]
+        );
+    }
+
+    #[test]
+    fn report_logical_errors() {
+        let source = "func main() {\n    var s = \"s\";\n    var b := not 1 || 2 == 3;\n    if s && b {\n        print b;\n    }\n    while not s {}\n}";
+        let (checker, _) = check(source);
+        let messages: Vec<&str> = checker
+            .diagnostics
+            .iter()
+            .map(|diag| diag.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Expected a condition found a value of type @str",
+                "Expected a condition found a value of type @str"
+            ]
+        );
+    }
+
+    #[test]
+    fn report_float_errors() {
+        let source = "func main() {\n    var x := 1.5;\n    print x % 2;\n    print !x;\n    print x, hex;\n    if x {}\n    var y @f32 = -x * 2;\n}";
+        let (checker, _) = check(source);
+        let messages: Vec<&str> = checker
+            .diagnostics
+            .iter()
+            .map(|diag| diag.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Can not apply (%) to values of type @f64 and @int",
+                "Can not apply (!) to a value of type @f64",
+                "Floats can only be printed as dec found @f64",
+                "Expected a condition found a value of type @f64",
+            ]
+        );
+    }
+
+    #[test]
+    fn report_cast_errors() {
+        let source = "func main() {\n    var a @[int,2];\n    var b = a as @int;\n    var p = 1.5 as @ptr;\n    var s = sizeof(@Nope);\n    var c = 'c' as @u8 as @f32;\n    var t = 5 as @str;\n}";
+        let (checker, _) = check(source);
+        let messages: Vec<&str> = checker
+            .diagnostics
+            .iter()
+            .map(|diag| diag.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Can not cast a value of type @[int,2] to @int",
+                "Can not cast a value of type @f64 to @ptr",
+                "Type (Nope) is not defined",
+                "Can not cast a value of type @int to @str",
+            ]
+        );
+    }
+
+    #[test]
+    fn report_array_literal_errors() {
+        let source = "struct P {\n    x @int,\n}\nfunc main() {\n    var a @[int,2] = [1, 2, 3];\n    var b = [];\n    var c @int = [1];\n    var d = [1, 'a', 2.5];\n    var e @[char,2] = \"abc\";\n    var f @[int,2] = a;\n    var g = [[1], [2, 3]];\n    var h @[P,1];\n    var i = [h[0]];\n    print [1, 2];\n}";
+        let (checker, _) = check(source);
+        let messages: Vec<&str> = checker
+            .diagnostics
+            .iter()
+            .map(|diag| diag.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Array literal has 3 elements but the array holds 2",
+                "Can not infer the type of an empty array literal",
+                "Can not initialize a value of type @int with an array literal",
+                "String literal has 3 bytes but the array holds 2",
+                "Can not copy a value of type @[int,2]",
+                "Mismatched types: expected @[int,1] found @[int,2]",
+                "Array of type @[P,1] can not be initialized with a literal",
+                "Array literals can only initialize array variables",
+            ]
+        );
+    }
+
+    #[test]
+    fn report_string_errors() {
+        let source = "func main() {\n    var s = \"abc\";\n    var p @ptr char = s;\n    var a = p[1..];\n    var n = len(3);\n    print s, hex;\n    var f = 1.5;\n    var b = f[0..1];\n    var c = s[0..'a'];\n    var d = s == 1;\n    var e @str = s[1..2];\n}";
+        let (checker, _) = check(source);
+        let messages: Vec<&str> = checker
+            .diagnostics
+            .iter()
+            .map(|diag| diag.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Slice of a pointer needs an end index",
+                "Can not take the length of a value of type @int",
+                "Strings can not be formatted",
+                "Can not slice a value of type @f64",
+                "Can not compare a value of type @str with @int",
+            ]
+        );
+    }
+
+    #[test]
+    fn report_slice_errors() {
+        let source = "func sum(a @[int,3]) {}\nfunc main() {\n    var a = [1, 2, 3];\n    var s @[int] = a[1..];\n    var t @[u8] = s;\n    print s;\n    var n = 5;\n    var x = n[1..2];\n    var g @[@[int,2],2];\n    for row in g[..] {}\n    var c @[char] = \"abc\";\n    var l = len(s) + len(c);\n}";
+        let (checker, _) = check(source);
+        let messages: Vec<&str> = checker
+            .diagnostics
+            .iter()
+            .map(|diag| diag.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Arrays can not be passed by value, use a slice for argument (a)",
+                "Mismatched types: expected @[u8] found @[int]",
+                "Can not print a value of type @[int]",
+                "Can not slice a value of type @int",
+                "Can not iterate over items of type @[int,2] by value",
+            ]
+        );
+    }
+
+    #[test]
+    fn report_constant_index_out_of_bounds() {
+        let source = "func main() {\n    var a @[int,3];\n    a[2] = 1;\n    a[3] = 1;\n    var b = a[-1];\n    var p = ptr a[0];\n    var c = p[5];\n}";
+        let (checker, _) = check(source);
+        let messages: Vec<&str> = checker
+            .diagnostics
+            .iter()
+            .map(|diag| diag.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Index 3 is out of bounds for an array of length 3",
+                "Index -1 is out of bounds for an array of length 3",
+            ]
+        );
+    }
+
+    #[test]
+    fn report_panic_errors() {
+        let source = "func main() {\n    var x = 1;\n    assert(x == 1, \"one\");\n    panic(x);\n    assert(\"yes\");\n    assert(x, 'c');\n}";
+        let (checker, _) = check(source);
+        let messages: Vec<&str> = checker
+            .diagnostics
+            .iter()
+            .map(|diag| diag.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Expected a message of type @str found @int",
+                "Expected a condition found a value of type @str",
+                "Expected a message of type @str found @char",
+            ]
+        );
+    }
+
+    #[test]
+    fn report_main_return_type() {
+        let (checker, _) = check("func main() @str {\n    return \"done\";\n}");
+        let messages: Vec<&str> = checker
+            .diagnostics
+            .iter()
+            .map(|diag| diag.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec!["main can only return an integer exit status found @str"]
+        );
+    }
+
+    #[test]
+    fn report_reserved_function_names() {
+        let (checker, _) = check("func __nmt_panic() {}\nfunc main() {}");
+        let messages: Vec<&str> = checker
+            .diagnostics
+            .iter()
+            .map(|diag| diag.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec!["Function (__nmt_panic) uses the prefix __nmt_ that is reserved for the runtime"]
+        );
+    }
+
+    #[test]
+    fn warn_about_uncovered_enum_variants() {
+        let source = "enum Op { A, B, C, D }\nfunc main() {\n    var o = Op::A;\n    match o {\n        Op::A => {}\n        Op::B..=Op::C => {}\n    }\n    match o {\n        Op::A => {}\n        _ => {}\n    }\n}";
+        let (checker, _) = check(source);
+        assert!(checker.diagnostics.is_empty());
+        let warnings: Vec<(&str, usize)> = checker
+            .warnings
+            .iter()
+            .map(|diag| (diag.message.as_str(), diag.span.as_ref().unwrap().line))
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![("Match over enum (Op) does not cover the variant(s) D", 4)]
+        );
+    }
+
+    #[test]
+    fn report_break_outside_loop() {
+        let (checker, _) = check("func main() {\n    while 1 { break; }\n    continue;\n}");
+        assert_eq!(checker.diagnostics.len(), 1);
+        assert_eq!(checker.diagnostics[0].message, "continue outside of a loop");
+    }
+
+    #[test]
+    fn report_pointer_errors() {
+        let source = "func main() {
+    var x = 1;
+    var p = ptr x;
+    var raw @ptr = p;
+    print *raw;
+    print ptr 2;
+    print x[0];
+    *p = 3;
+}";
+        let (checker, _) = check(source);
+        let messages: Vec<_> = checker.diagnostics.iter().map(|d| &d.message).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Can not dereference an untyped pointer",
+                "Can not take the address of a value",
+                "Can not index a value of type @int",
+            ]
+        );
+    }
+
+    #[test]
+    fn report_struct_errors() {
+        let source = "struct Node {
+    value @int,
+    next @Node,
+}
+struct Point { x @int, y @int }
+func f(p @Point) {}
+func main() {
+    var p @Point;
+    p.z = 1;
+    p = 2;
+    var q = ptr p;
+    q.x = 3;
+}";
+        let (checker, _) = check(source);
+        let lines: Vec<usize> = checker
+            .diagnostics
+            .iter()
+            .map(|d| d.span.as_ref().unwrap().line)
+            .collect();
+        assert_eq!(lines, vec![1, 6, 9, 10]);
+    }
+
+    #[test]
+    fn report_unknown_types() {
+        let source = "struct Point { x @int, y @Pont }
+func f(a @ptr Q) @Z {
+    return 1;
+}
+func main() {
+    var k @Q;
+    k = 5;
+    var c = 1 as @W;
+    var s = sizeof(@[Nope,2]);
+}";
+        let (checker, _) = check(source);
+        let messages: Vec<&str> = checker
+            .diagnostics
+            .iter()
+            .map(|diag| diag.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Type (Pont) is not defined",
+                "Type (Q) is not defined",
+                "Type (Z) is not defined",
+                "Type (Q) is not defined",
+                "Type (W) is not defined",
+                "Type (Nope) is not defined",
+            ]
+        );
+    }
+
+    #[test]
+    fn report_match_errors() {
+        let source = "enum Color { Red, Green = 1 + 1, Blue = Red }
+func main() {
+    var x = 3;
+    match x {
+        x => {}
+        Color::Red => {}
+        5..5 => {}
+        _ => {}
+        _ => {}
+        -5..-9223372036854775807 - 1 => {}
+    }
+    match Color::Pink {}
+}";
+        let (checker, _) = check(source);
+        let lines: Vec<usize> = checker
+            .diagnostics
+            .iter()
+            .map(|d| d.span.as_ref().unwrap().line)
+            .collect();
+        assert_eq!(lines, vec![1, 5, 6, 7, 9, 10, 12]);
+    }
+
+    #[test]
+    fn report_for_errors() {
+        let source = "struct Point { x @int }
+func main() {
+    var x = 3;
+    for i in x {}
+    for i in 0..10 step 0 {}
+    for i in 0..10 {
+        i = 2;
+    }
+    var points @[Point,2];
+    for p in points {}
+    for c in \"ab\"..\"cd\" {}
+}";
+        let (checker, _) = check(source);
+        let lines: Vec<usize> = checker
+            .diagnostics
+            .iter()
+            .map(|d| d.span.as_ref().unwrap().line)
+            .collect();
+        assert_eq!(lines, vec![4, 5, 7, 10, 11]);
+    }
+
+    #[test]
+    fn report_non_constant_globals() {
+        let source = "func f() @int { return 1; }\nvar a = f();\nvar b := 2 * 3;\nfunc main() {\n    var c :: b;\n    b = 1;\n}";
+        let (checker, _) = check(source);
+        let lines: Vec<usize> = checker
+            .diagnostics
+            .iter()
+            .map(|diag| diag.span.as_ref().unwrap().line)
+            .collect();
+        assert_eq!(lines, vec![2, 5, 6]);
+    }
+}
@@ -1,5 +1,6 @@
 use std::process::exit;
 
+use crate::compiler::BuildOptions;
 use crate::{COPYRIGHT, DEBUG, VERSION};
 
 pub fn help_command() {
@@ -12,9 +13,57 @@ pub fn help_command() {
     println!("\nnemet [Command] <path> (Options)");
     println!("Commands:");
     println!("\t{} Show help", padding_right("help"));
+    println!(
+        "\t{} Report all errors and warnings without compiling",
+        padding_right("check")
+    );
     println!("Options:");
     println!("\t{} Show help", padding_right("--help"));
     println!("\t{} Show Version", padding_right("--version"));
+    println!("Build Options:");
+    println!("\t{} Leave out runtime checks", padding_right("--release"));
+    println!(
+        "\t{} Check indices at runtime, default in debug",
+        padding_right("--bounds-checks")
+    );
+    println!(
+        "\t{} Do not check indices at runtime",
+        padding_right("--no-bounds-checks")
+    );
+    println!(
+        "\t{} Check arithmetic at runtime, default in debug",
+        padding_right("--overflow-checks")
+    );
+    println!(
+        "\t{} Let integer arithmetic wrap",
+        padding_right("--no-overflow-checks")
+    );
+}
+
+/// Reads the build options that come after the path
+/// Builds are in debug mode unless --release is given
+pub fn build_options(arg: &CliArgs) -> BuildOptions {
+    let flags = arg.args.get(arg.index + 1..).unwrap_or_default();
+    let mut options = if flags.iter().any(|flag| flag == "--release") {
+        BuildOptions::release()
+    } else {
+        BuildOptions::debug()
+    };
+    for flag in flags {
+        match flag.as_str() {
+            "--release" => {}
+            "--bounds-checks" => options.bounds_checks = true,
+            "--no-bounds-checks" => options.bounds_checks = false,
+            "--overflow-checks" => options.overflow_checks = true,
+            "--no-overflow-checks" => options.overflow_checks = false,
+            _ => {
+                eprintln!("[error] Unknown build option ({flag})");
+                help_command();
+                exit(1);
+            }
+        }
+    }
+    options
 }
 
 pub fn padding_right(str: &str) -> String {
@@ -1,16 +1,21 @@
 use std::collections::HashMap;
-use std::error::Error;
-use std::process::exit;
 
 use crate::asm_generator::x86_64_nasm_generator;
+use crate::checker::check_file;
+use crate::diagnostic::{Diagnostic, Span};
 use crate::parser::block::Block;
-use crate::parser::expr::{CompareOp, Expr, FunctionCall, Op, UnaryExpr};
+use crate::parser::enums::EnumDef;
+use crate::parser::expr::{
+    BinaryExpr, Cast, CompareExpr, CompareOp, Expr, ExprKind, FieldAccess, FunctionCall, LogicalOp,
+    Op, SizeOf, Slice, UnaryExpr,
+};
 use crate::parser::function::{Function, FunctionArg};
-use crate::parser::parse_file;
 use crate::parser::program::ProgramItem;
 use crate::parser::stmt::{
-    Assgin, AssginOp, ElseBlock, IFStmt, Stmt, VariableDeclare, VariableType, WhileStmt,
+    Assgin, AssginOp, ElseBlock, ForIter, ForStmt, IFStmt, MatchStmt, Pattern, PrintFormat,
+    PrintStmt, Stmt, StmtKind, VariableDeclare, VariableType, WhileStmt,
 };
+use crate::parser::structs::StructDef;
 
 macro_rules! asm {
     ($($arg:tt)+) => (
@@ -18,12 +23,41 @@ macro_rules! asm {
     );
 }
 
-pub fn compile_to_asm(path: String) {
-    let mut compiler = Compiler::new();
-    let (instr_buf, data_buf) = compiler
-        .compile(path.clone())
-        .expect("Can not Compile Program");
-    x86_64_nasm_generator(path, instr_buf, data_buf).unwrap();
+/// Runtime checks that are compiled into the program
+#[derive(Debug, Clone, Copy)]
+pub struct BuildOptions {
+    /// Checks indices of arrays, strings and slices against their length
+    pub bounds_checks: bool,
+    /// Checks integer arithmetic for overflow and division by zero
+    pub overflow_checks: bool,
+}
+impl BuildOptions {
+    /// Every runtime check is enabled
+    pub fn debug() -> Self {
+        Self {
+            bounds_checks: true,
+            overflow_checks: true,
+        }
+    }
+
+    /// Runtime checks are left out
+    pub fn release() -> Self {
+        Self {
+            bounds_checks: false,
+            overflow_checks: false,
+        }
+    }
+}
+
+pub fn compile_to_asm(path: String, options: BuildOptions) -> Result<(), Vec<Diagnostic>> {
+    let mut compiler = Compiler::new(options);
+    let (instr_buf, data_buf, bss_buf) = compiler.compile(path.clone())?;
+    for warning in &compiler.warnings {
+        eprint!("{}", warning.render());
+    }
+    x86_64_nasm_generator(path, instr_buf, data_buf, bss_buf)
+        .map_err(|err| Diagnostic::error(format!("Can not generate asm file: {err}")))?;
+    Ok(())
 }
 
 pub fn mem_word(size: usize) -> String {
@@ -38,6 +72,146 @@ pub fn mem_word(size: usize) -> String {
     }
 }
 
+/// Loads a memory operand of the given size into rax
+/// Values smaller than 8 bytes are sign or zero extended
+pub fn load_rax(mem_acss: &str, size: usize, signed: bool) -> String {
+    match (size, signed) {
+        (1 | 2, true) => asm!("movsx rax, {mem_acss}"),
+        (1 | 2, false) => asm!("movzx rax, {mem_acss}"),
+        (4, true) => asm!("movsxd rax, {mem_acss}"),
+        (4, false) => asm!("mov eax, {mem_acss}"),
+        _ => asm!("mov rax, {mem_acss}"),
+    }
+}
+
+/// Suffix of the SSE instructions for a float type
+fn float_suffix(t: &VariableType) -> &'static str {
+    match t {
+        VariableType::F32 => "ss",
+        _ => "sd",
+    }
+}
+
+/// Moves the bits of a float from a general register to an xmm register
+fn to_xmm(xmm: &str, register: &str, t: &VariableType) -> String {
+    match t {
+        VariableType::F32 => asm!("movd {xmm}, {}", rbs(register, 4)),
+        _ => asm!("movq {xmm}, {}", rbs(register, 8)),
+    }
+}
+
+/// Moves the bits of a float from an xmm register to a general register
+fn from_xmm(register: &str, xmm: &str, t: &VariableType) -> String {
+    match t {
+        VariableType::F32 => asm!("movd {}, {xmm}", rbs(register, 4)),
+        _ => asm!("movq {}, {xmm}", rbs(register, 8)),
+    }
+}
+
+/// Where an argument is passed in a call
+enum ArgLocation {
+    Register(String),
+    /// Index of the argument among the ones pushed on the stack
+    Stack(usize),
+}
+
+/// Number of 8 byte stack slots a value of the type takes in the stack machine
+/// Strings and slices are pushed as their address followed by their length
+fn slot_count(t: &VariableType) -> usize {
+    if t.is_view() {
+        2
+    } else {
+        1
+    }
+}
+
+/// System V: floats are passed in xmm0-7 and the other arguments in the six
+/// integer registers, the arguments that do not fit are passed on the stack
+/// Returns a location for every slot, a string takes two registers or none
+fn arg_locations(args: &[FunctionArg]) -> Vec<ArgLocation> {
+    let (mut ints, mut floats, mut stack) = (0, 0, 0);
+    let mut locations = Vec::new();
+    for arg in args {
+        let slots = slot_count(&arg.typedef);
+        if arg.typedef.is_float() && floats < 8 {
+            floats += 1;
+            locations.push(ArgLocation::Register(format!("xmm{}", floats - 1)));
+        } else if !arg.typedef.is_float() && ints + slots <= 6 {
+            for _ in 0..slots {
+                ints += 1;
+                locations.push(ArgLocation::Register(function_args_register(ints - 1, 8)));
+            }
+        } else {
+            for _ in 0..slots {
+                stack += 1;
+                locations.push(ArgLocation::Stack(stack - 1));
+            }
+        }
+    }
+    locations
+}
+
+/// Suffix of the jcc, setcc or cmovcc instruction for a compare
+pub fn condition_code(op: &CompareOp, unsigned: bool) -> &'static str {
+    match (op, unsigned) {
+        (CompareOp::Eq, _) => "e",
+        (CompareOp::NotEq, _) => "ne",
+        (CompareOp::Bigger, false) => "g",
+        (CompareOp::Smaller, false) => "l",
+        (CompareOp::BiggerEq, false) => "ge",
+        (CompareOp::SmallerEq, false) => "le",
+        (CompareOp::Bigger, true) => "a",
+        (CompareOp::Smaller, true) => "b",
+        (CompareOp::BiggerEq, true) => "ae",
+        (CompareOp::SmallerEq, true) => "be",
+    }
+}
+
+/// Returns true if the operands of the compare are compared as unsigned values
+/// Pointers are addresses and integers are converted to their common type first
+fn is_unsigned_compare(c: &CompareExpr) -> bool {
+    match (&c.left.v_type, &c.right.v_type) {
+        (Some(VariableType::Pointer(_)), _) | (_, Some(VariableType::Pointer(_))) => true,
+        (Some(l), Some(r)) if l.is_numeric() && r.is_numeric() => !l.arith_type(r).is_signed(),
+        _ => false,
+    }
+}
+
+/// Truncates rax to the size of an integer and extends it back to 64 bits
+fn extend_rax(size: usize, signed: bool) -> String {
+    match (size, signed) {
+        (1, true) => asm!("movsx rax, al"),
+        (1, false) => asm!("movzx rax, al"),
+        (2, true) => asm!("movsx rax, ax"),
+        (2, false) => asm!("movzx rax, ax"),
+        (4, true) => asm!("movsxd rax, eax"),
+        (4, false) => asm!("mov eax, eax"),
+        _ => {
+            unreachable!("Incurrect Size")
+        }
+    }
+}
+
+/// Returns the jump that keeps a range loop running while the counter has not
+/// passed the end, a loop counting down runs while the counter is above it
+fn range_jump(down: bool, inclusive: bool, signed: bool) -> &'static str {
+    match (down, inclusive, signed) {
+        (false, false, true) => "jl",
+        (false, true, true) => "jle",
+        (true, false, true) => "jg",
+        (true, true, true) => "jge",
+        (false, false, false) => "jb",
+        (false, true, false) => "jbe",
+        (true, false, false) => "ja",
+        (true, true, false) => "jae",
+    }
+}
+
+/// Returns true if the checked expression holds a signed value
+fn is_signed(expr: &Expr) -> bool {
+    expr.v_type.as_ref().is_none_or(|t| t.is_signed())
+}
+
 pub fn rbs(register: &str, size: usize) -> String {
     match register {
         "a" | "b" | "c" | "d" => match size {
@@ -94,6 +268,9 @@ pub fn function_args_register(arg_numer: usize, size: usize) -> String {
     }
 }
 
+/// Instructions, data section and bss section of the compiled program
+type CompileResult = Result<(Vec<String>, Vec<String>, Vec<String>), Vec<Diagnostic>>;
+
 #[derive(Debug, Clone)]
 pub struct VariableMap {
     _ident: String,
@@ -101,133 +278,433 @@ pub struct VariableMap {
     size: usize,
     item_size: usize,
     is_mut: bool,
+    /// Data section label for global and static variables
+    label: Option<String>,
+}
+impl VariableMap {
+    /// Returns the address of the variable e.g: rbp-16 or var3
+    fn addr(&self) -> String {
+        match &self.label {
+            Some(label) => label.clone(),
+            None => format!("rbp-{}", self.offset + self.size),
+        }
+    }
 }
 
 pub struct Compiler {
+    pub warnings: Vec<Diagnostic>,
     instruct_buf: Vec<String>,
     data_buf: Vec<String>,
+    bss_buf: Vec<String>,
     scoped_blocks: Vec<usize>,
     block_id: usize,
     variables_map: HashMap<String, VariableMap>,
+    globals_map: HashMap<String, VariableMap>,
+    /// Globals of every compiled library by path so later imports share them
+    lib_globals: HashMap<String, HashMap<String, VariableMap>>,
     functions_map: HashMap<String, Function>,
+    structs_map: HashMap<String, StructDef>,
+    enums_map: HashMap<String, EnumDef>,
     mem_offset: usize,
+    loop_tags: Vec<usize>,
+    /// Return type of the function that is being compiled
+    ret_type: Option<VariableType>,
+    /// Counter for the labels of && || and not, they can nest at the same
+    /// position of instruct_buf so the length can not be used as a tag
+    logic_tags: usize,
+    options: BuildOptions,
 }
 
 impl Compiler {
     // TODO: handle Error for Parsing
-    pub fn new() -> Self {
+    pub fn new(options: BuildOptions) -> Self {
         Self {
+            warnings: Vec::new(),
             instruct_buf: Vec::new(),
             data_buf: Vec::new(),
+            bss_buf: Vec::new(),
             scoped_blocks: Vec::new(),
             block_id: 0,
             variables_map: HashMap::new(),
+            globals_map: HashMap::new(),
+            lib_globals: HashMap::new(),
             functions_map: HashMap::new(),
+            structs_map: HashMap::new(),
+            enums_map: HashMap::new(),
             mem_offset: 0,
+            loop_tags: Vec::new(),
+            ret_type: None,
+            logic_tags: 0,
+            options,
         }
     }
 
+    /// Records a warning pointing to a piece of code
+    fn warning(&mut self, span: &Span, message: impl ToString) {
+        let warning = Diagnostic::warning(message).with_span(span.clone());
+        self.warnings.push(warning);
+    }
+
+    /// Size of the local variables rounded up to keep rsp 16 bytes aligned
     fn frame_size(&self) -> usize {
-        2 << self.mem_offset.ilog2() as usize
+        (self.mem_offset + 15) & !15
     }
 
     pub fn find_variable(&self, ident: String) -> Option<VariableMap> {
-        for block_id in &self.scoped_blocks {
+        // Inner blocks shadow the outer ones
+        for block_id in self.scoped_blocks.iter().rev() {
             let map_ident = format!("{ident}%{}", block_id);
             let map = self.variables_map.get(&map_ident);
             if let Some(map) = map {
                 return Some(map.clone());
             }
         }
-        None
+        self.globals_map.get(&ident).cloned()
     }
 
-    pub fn insert_variable(&mut self, var: &VariableDeclare) {
-        let ident: String;
-        let var_map: VariableMap;
-        let mut size = 8;
-        let mut item_size = 8;
-        if var.v_type.is_some() {
-            let typ = var.v_type.clone().unwrap();
-            if let VariableType::Array(a, s) = typ {
-                match *a.as_ref() {
-                    VariableType::Int => {
-                        size = 8 * s;
-                        item_size = 8;
-                    }
-                    VariableType::Char => {
-                        size = s;
-                        item_size = 1;
-                    }
-                    _ => {
-                        todo!("Unsuported Array Type");
-                    }
-                }
-            }
-        }
+    pub fn insert_variable(&mut self, var: &VariableDeclare) -> Result<(), Diagnostic> {
         if var.is_static {
-            todo!();
-        } else {
-            ident = format!("{}%{}", var.ident, self.block_id);
-            var_map = VariableMap {
-                _ident: var.ident.clone(),
-                offset: self.mem_offset,
-                // TODO: Change size
-                size,
-                item_size,
-                is_mut: var.mutable,
-            };
+            let var_map = self.static_variable(var)?;
+            let ident = format!("{}%{}", var.ident, self.block_id);
+            self.variables_map.insert(ident, var_map);
+            return Ok(());
         }
+        let (size, item_size) = self.variable_size(var)?;
+        let ident = format!("{}%{}", var.ident, self.block_id);
+        let var_map = VariableMap {
+            _ident: var.ident.clone(),
+            offset: self.mem_offset,
+            size,
+            item_size,
+            is_mut: var.mutable,
+            label: None,
+        };
         self.mem_offset += size;
-        if var.init_value.is_some() {
-            // TODO: Type check
+        if let (Some(t @ VariableType::Array(_, _)), Some(init)) = (&var.v_type, &var.init_value) {
+            let addr = var_map.addr();
+            if !Self::fills_array(t, init) {
+                // Elements missing from the literal are zeroed
+                self.instruct_buf.push(asm!("lea rdi, [{addr}]"));
+                self.instruct_buf.push(asm!("mov rcx, {size}"));
+                self.instruct_buf.push(asm!("xor eax, eax"));
+                self.instruct_buf.push(asm!("rep stosb"));
+            }
+            self.compile_array_init(&addr, t, init)?;
+        } else if var.init_value.is_some() {
             let init_value = var.init_value.clone().unwrap();
             // this pushes result in stack
-            self.compile_expr(&init_value);
-            let mem_acss = format!(
-                "{} [rbp-{}]",
-                mem_word(var_map.item_size),
-                var_map.offset + var_map.size
-            );
-            self.instruct_buf.push(asm!("pop rax"));
-            self.instruct_buf
-                .push(asm!("mov {mem_acss},{}", rbs("a", var_map.item_size)));
+            self.compile_expr(&init_value)?;
+            if let Some(t) = &var.v_type {
+                self.compile_convert(&init_value.v_type, t);
+            }
+            if var.v_type.as_ref().is_some_and(|t| t.is_view()) {
+                self.store_view(&var_map.addr());
+            } else {
+                let mem_acss = format!("{} [{}]", mem_word(var_map.item_size), var_map.addr());
+                self.instruct_buf.push(asm!("pop rax"));
+                self.instruct_buf
+                    .push(asm!("mov {mem_acss},{}", rbs("a", var_map.item_size)));
+            }
         }
         self.variables_map.insert(ident, var_map);
+        Ok(())
+    }
+
+    /// Stores the string or the slice on top of the stack at addr
+    fn store_view(&mut self, addr: &str) {
+        self.instruct_buf.push(asm!("pop rcx"));
+        self.instruct_buf.push(asm!("pop rax"));
+        self.instruct_buf.push(asm!("mov qword [{addr}], rax"));
+        self.instruct_buf.push(asm!("mov qword [{addr}+8], rcx"));
+    }
+
+    /// Returns true if the literal sets every element of the array
+    fn fills_array(t: &VariableType, init: &Expr) -> bool {
+        match (t, &init.kind) {
+            (VariableType::Array(t, len), ExprKind::ArrayLiteral(items)) => {
+                items.len() == *len && items.iter().all(|item| Self::fills_array(t, item))
+            }
+            (VariableType::Array(_, len), ExprKind::String(str)) => str.len() == *len,
+            _ => true,
+        }
+    }
+
+    /// Stores an array literal or a string element by element starting at addr
+    fn compile_array_init(
+        &mut self,
+        addr: &str,
+        t: &VariableType,
+        init: &Expr,
+    ) -> Result<(), Diagnostic> {
+        match (t, &init.kind) {
+            (VariableType::Array(t, _), ExprKind::ArrayLiteral(items)) => {
+                let item_size = self.type_size(t);
+                for (i, item) in items.iter().enumerate() {
+                    self.compile_array_init(&format!("{addr}+{}", i * item_size), t, item)?;
+                }
+            }
+            (VariableType::Array(_, _), ExprKind::String(_)) => {
+                // pushes the string data and its length
+                self.compile_expr(init)?;
+                self.instruct_buf.push(asm!("pop rcx"));
+                self.instruct_buf.push(asm!("pop rsi"));
+                self.instruct_buf.push(asm!("lea rdi, [{addr}]"));
+                self.instruct_buf.push(asm!("rep movsb"));
+            }
+            (t, _) if t.is_view() => {
+                self.compile_expr(init)?;
+                self.store_view(addr);
+            }
+            _ => {
+                let size = self.type_size(t);
+                self.compile_expr(init)?;
+                self.compile_convert(&init.v_type, t);
+                self.instruct_buf.push(asm!("pop rax"));
+                self.instruct_buf
+                    .push(asm!("mov {} [{addr}],{}", mem_word(size), rbs("a", size)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the total size and the item size of a variable in bytes
+    fn variable_size(&self, var: &VariableDeclare) -> Result<(usize, usize), Diagnostic> {
+        match &var.v_type {
+            Some(VariableType::Array(t, s)) => {
+                let item_size = self.type_size(t);
+                match t.as_ref() {
+                    VariableType::Array(_, _) | VariableType::Struct(_) => Ok((item_size * s, 8)),
+                    _ => Ok((item_size * s, item_size)),
+                }
+            }
+            Some(t @ VariableType::Struct(_)) => Ok((self.type_size(t), 8)),
+            // The address and the length of a string or a slice
+            Some(t) if t.is_view() => Ok((16, 8)),
+            // Scalars take a full slot and are accessed with their own size
+            Some(t) => Ok((8, self.type_size(t))),
+            None => Ok((8, 8)),
+        }
+    }
+
+    /// Size of a value of the type in memory
+    fn type_size(&self, t: &VariableType) -> usize {
+        if let Some(size) = t.int_size() {
+            return size;
+        }
+        match t {
+            VariableType::F32 => 4,
+            VariableType::String | VariableType::Slice(_) => 16,
+            VariableType::Array(t, s) => self.type_size(t) * s,
+            VariableType::Struct(ident) => self.struct_layout(ident, None).0,
+            _ => 8,
+        }
+    }
+
+    /// Alignment of a value of the type in memory
+    fn type_align(&self, t: &VariableType) -> usize {
+        if let Some(size) = t.int_size() {
+            return size;
+        }
+        match t {
+            VariableType::F32 => 4,
+            VariableType::Array(t, _) => self.type_align(t),
+            VariableType::Struct(ident) => self.structs_map[ident]
+                .fields
+                .iter()
+                .map(|f| self.type_align(&f.typedef))
+                .max()
+                .unwrap_or(1),
+            _ => 8,
+        }
+    }
+
+    /// Lays out the fields of a struct in order, each aligned to its own alignment
+    /// Returns the size of the struct or the offset of the given field
+    fn struct_layout(&self, ident: &str, field: Option<&str>) -> (usize, Option<VariableType>) {
+        let mut offset: usize = 0;
+        for f in self.structs_map[ident].fields.iter() {
+            let align = self.type_align(&f.typedef);
+            offset = offset.div_ceil(align) * align;
+            if Some(f.ident.as_str()) == field {
+                return (offset, Some(f.typedef.clone()));
+            }
+            offset += self.type_size(&f.typedef);
+        }
+        let align = self.type_align(&VariableType::Struct(ident.to_string()));
+        (offset.div_ceil(align) * align, None)
+    }
+
+    /// Adds the struct and enum definitions of a program
+    fn type_defs(&mut self, items: &[ProgramItem]) {
+        for item in items {
+            match item {
+                ProgramItem::Struct(s) => {
+                    self.structs_map.insert(s.ident.clone(), s.clone());
+                }
+                ProgramItem::Enum(e) => {
+                    self.enums_map.insert(e.ident.clone(), e.clone());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Places a global or static variable in .data or .bss section
+    /// Initial value has to be known at compile time
+    fn static_variable(&mut self, var: &VariableDeclare) -> Result<VariableMap, Diagnostic> {
+        let (size, item_size) = self.variable_size(var)?;
+        let label = format!("var{}", self.data_buf.len() + self.bss_buf.len());
+        match &var.init_value {
+            Some(init_value) => {
+                let t = var.v_type.clone().unwrap_or(VariableType::Int);
+                let mut values = Vec::new();
+                if self
+                    .static_data(&t, Some(init_value), &mut values)
+                    .is_none()
+                {
+                    return Err(Diagnostic::error(format!(
+                        "Initial value of static variable ({}) is not a compile time constant",
+                        var.ident
+                    ))
+                    .with_span(init_value.span.clone()));
+                }
+                let mut item = &t;
+                while let VariableType::Array(t, _) = item {
+                    item = t;
+                }
+                let directive = match self.type_size(item) {
+                    1 => "db",
+                    2 => "dw",
+                    4 => "dd",
+                    _ => "dq",
+                };
+                self.data_buf
+                    .push(asm!("{label} {directive} {}", values.join(", ")));
+            }
+            None => {
+                self.bss_buf.push(asm!("{label} resb {size}"));
+            }
+        }
+        Ok(VariableMap {
+            _ident: var.ident.clone(),
+            offset: 0,
+            size,
+            item_size,
+            is_mut: var.mutable,
+            label: Some(label),
+        })
     }
 
-    pub fn function_args(&mut self, args: &[FunctionArg]) {
-        for (args_count, arg) in args.iter().enumerate() {
+    /// Flattens the initial value of a static variable into the values of its items
+    /// Missing array elements are zero, returns None if the value is not constant
+    fn static_data(
+        &mut self,
+        t: &VariableType,
+        init: Option<&Expr>,
+        values: &mut Vec<String>,
+    ) -> Option<()> {
+        let Some(init) = init else {
+            values.extend(std::iter::repeat_n("0".to_string(), Self::item_count(t)));
+            return Some(());
+        };
+        match (t, &init.kind) {
+            (VariableType::Array(t, len), ExprKind::ArrayLiteral(items)) => {
+                for i in 0..*len {
+                    self.static_data(t, items.get(i), values)?;
+                }
+            }
+            (VariableType::Array(_, len), ExprKind::String(str)) => {
+                values.extend(str.bytes().map(|b| b.to_string()));
+                values.extend(std::iter::repeat_n("0".to_string(), len - str.len()));
+            }
+            (VariableType::String, ExprKind::String(str)) => {
+                let id = self.string_data(str);
+                values.push(format!("data{id}"));
+                values.push(format!("len{id}"));
+            }
+            (VariableType::F32, _) => {
+                values.push(((init.const_float()? as f32).to_bits() as i64).to_string());
+            }
+            (VariableType::F64, _) => values.push(init.const_float()?.to_bits().to_string()),
+            _ => values.push(
+                init.const_value()
+                    .or(init.const_float().map(|x| x as i64))?
+                    .to_string(),
+            ),
+        }
+        Some(())
+    }
+
+    /// Number of 8 byte or smaller items in a value of the type
+    fn item_count(t: &VariableType) -> usize {
+        match t {
+            VariableType::Array(t, len) => len * Self::item_count(t),
+            t => slot_count(t),
+        }
+    }
+
+    /// Adds the top level variables of a program to the globals
+    fn global_variables(&mut self, items: &[ProgramItem]) -> Result<(), Diagnostic> {
+        for item in items {
+            if let ProgramItem::StaticVar(var) = item {
+                let var_map = self.static_variable(var)?;
+                self.globals_map.insert(var.ident.clone(), var_map);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn function_args(&mut self, args: &[FunctionArg]) -> Result<(), Diagnostic> {
+        let mut locations = arg_locations(args).into_iter();
+        for arg in args {
             let ident = format!("{}%{}", arg.ident, self.block_id);
+            let (size, item_size) = match arg.typedef {
+                ref t if t.is_view() => (16, 8),
+                // The slot is written whole and read with the size of the type
+                _ => (8, self.type_size(&arg.typedef)),
+            };
             let map = VariableMap {
                 _ident: arg.ident.clone(),
                 offset: self.mem_offset,
                 is_mut: false,
-                item_size: 8,
-                size: 8,
+                item_size,
+                size,
+                label: None,
             };
-            if args_count < 6 {
-                let mem_acss = format!("{} [rbp-{}]", mem_word(8), map.offset + map.size);
-                let reg = function_args_register(args_count, 8);
-                self.instruct_buf.push(asm!("mov {},{}", mem_acss, reg));
-            } else {
-                todo!();
-                // let mem_overload = format!("{} [rbp+{}]", mem_word(8), 16 + (args_count - 6) * 8);
-                //let mem_acss = format!("{} [rbp-{}]", mem_word(8), map.offset + map.size);
-                //self.instruct_buf
-                //    .push(asm!("mov {},{}", mem_acss, mem_overload));
+            for slot in 0..slot_count(&arg.typedef) {
+                let location = locations.next().expect("a location for every slot");
+                let mem_acss = match slot {
+                    0 => format!("{} [{}]", mem_word(8), map.addr()),
+                    _ => format!("{} [{}+{}]", mem_word(8), map.addr(), slot * 8),
+                };
+                match location {
+                    ArgLocation::Register(reg) if reg.starts_with("xmm") => {
+                        self.instruct_buf.push(asm!("movq {},{}", mem_acss, reg));
+                    }
+                    ArgLocation::Register(reg) => {
+                        self.instruct_buf.push(asm!("mov {},{}", mem_acss, reg));
+                    }
+                    ArgLocation::Stack(index) => {
+                        // Pushed by the caller above the return address and saved rbp
+                        let mem_overload = format!("{} [rbp+{}]", mem_word(8), 16 + index * 8);
+                        self.instruct_buf.push(asm!("mov rax,{}", mem_overload));
+                        self.instruct_buf.push(asm!("mov {},rax", mem_acss));
+                    }
+                }
             }
             self.variables_map.insert(ident, map);
-            self.mem_offset += 8;
+            self.mem_offset += size;
         }
+        Ok(())
     }
 
-    pub fn function(&mut self, f: Function) {
+    pub fn function(&mut self, f: Function) -> Result<(), Diagnostic> {
         self.scoped_blocks = Vec::new();
         self.block_id = 0;
         self.scoped_blocks.push(0);
         self.mem_offset = 0;
         self.variables_map = HashMap::new();
+        self.ret_type = f.ret_type.clone();
         if f.ident == "main" {
             self.instruct_buf.push("_start:\n".to_string());
         } else {
@@ -235,57 +712,71 @@ impl Compiler {
         }
 
         // set rbp to stack pointer for this block
-        let index_1 = self.instruct_buf.len();
-        self.instruct_buf.push(String::new());
-        let index_2 = self.instruct_buf.len();
-        self.instruct_buf.push(String::new());
-        let index_3 = self.instruct_buf.len();
+        self.instruct_buf.push(asm!("push rbp"));
+        self.instruct_buf.push(asm!("mov rbp, rsp"));
+        // frame size is known after compiling the block
+        let frame_index = self.instruct_buf.len();
         self.instruct_buf.push(String::new());
 
-        self.function_args(&f.args);
-        self.compile_block(&f.block);
+        self.function_args(&f.args)?;
+        self.compile_block(&f.block)?;
         self.scoped_blocks.pop();
-        // Call Exit Syscall
-        if !self.variables_map.is_empty() {
-            self.instruct_buf[index_1] = asm!("push rbp");
-            self.instruct_buf[index_2] = asm!("mov rbp, rsp");
-            self.instruct_buf[index_3] = asm!("sub rsp, {}", self.frame_size());
+        if self.frame_size() != 0 {
+            self.instruct_buf[frame_index] = asm!("sub rsp, {}", self.frame_size());
         }
+        let returns_status = f.ident == "main" && f.ret_type.is_some();
+        if returns_status {
+            // Falling off the end of main exits with 0
+            self.instruct_buf.push(asm!("mov rax, 0"));
+        }
+        // Every return jumps here
+        self.instruct_buf.push(asm!(".Lreturn:"));
         if f.ident == "main" {
+            // Call Exit Syscall with the returned value as the status
+            if returns_status {
+                self.instruct_buf.push(asm!("mov rdi, rax"));
+            } else {
+                self.instruct_buf.push(asm!("mov rdi, 0"));
+            }
             self.instruct_buf.push(asm!("mov rax, 60"));
-            self.instruct_buf.push(asm!("mov rdi, 0"));
             self.instruct_buf.push(asm!("syscall"));
         } else {
             // revert rbp
-            if !self.variables_map.is_empty() {
-                //self.instruct_buf.push(asm!("pop rbp"));
-                self.instruct_buf.push(asm!("leave"));
-                self.instruct_buf.push(asm!("ret"));
-            } else {
-                self.instruct_buf.push(asm!("ret"));
-            }
+            self.instruct_buf.push(asm!("leave"));
+            self.instruct_buf.push(asm!("ret"));
         }
+        Ok(())
     }
 
-    pub fn compile_lib(
-        &mut self,
-        path: String,
-        exports: Vec<String>,
-    ) -> Result<(Vec<String>, Vec<String>), Box<dyn Error>> {
-        let program = parse_file(path);
+    pub fn compile_lib(&mut self, path: String, exports: Vec<String>) -> CompileResult {
+        let (program, warnings) = check_file(path.clone())?;
+        self.warnings.extend(warnings);
+        // Globals of the library are only visible to its own functions
+        let globals_map = std::mem::take(&mut self.globals_map);
+        self.type_defs(&program.items);
+        match self.lib_globals.get(&path) {
+            Some(globals) => self.globals_map = globals.clone(),
+            None => {
+                self.global_variables(&program.items)?;
+                self.lib_globals.insert(path, self.globals_map.clone());
+            }
+        }
+        for item in &program.items {
+            if let ProgramItem::Func(f) = item {
+                if exports.contains(&f.ident) {
+                    self.functions_map.insert(f.ident.clone(), f.clone());
+                }
+            }
+        }
         for item in program.items {
             match item {
-                ProgramItem::StaticVar(_s) => {
-                    todo!();
-                    // self.insert_variable(&s);
-                }
+                ProgramItem::StaticVar(_) | ProgramItem::Struct(_) | ProgramItem::Enum(_) => {}
                 ProgramItem::Func(f) => {
                     if exports.contains(&f.ident) {
-                        self.functions_map.insert(f.ident.clone(), f.clone());
-                        self.function(f);
+                        self.function(f)?;
                     }
                 }
-                ProgramItem::Import(next_path, idents) => {
+                ProgramItem::Import(next_path, idents, _) => {
                     let mut new_path = String::new();
                     new_path.push_str(next_path.as_str());
                     new_path.push_str(".nmt");
@@ -293,23 +784,32 @@ impl Compiler {
                 }
             }
         }
-        Ok((self.instruct_buf.clone(), self.data_buf.clone()))
+        self.globals_map = globals_map;
+        Ok((
+            self.instruct_buf.clone(),
+            self.data_buf.clone(),
+            self.bss_buf.clone(),
+        ))
     }
 
-    // TODO: Handle Compilation Error
-    pub fn compile(&mut self, path: String) -> Result<(Vec<String>, Vec<String>), Box<dyn Error>> {
-        let program = parse_file(path);
+    pub fn compile(&mut self, path: String) -> CompileResult {
+        let (program, warnings) = check_file(path)?;
+        self.warnings.extend(warnings);
+        self.type_defs(&program.items);
+        self.global_variables(&program.items)?;
+        // Functions are known before any body is compiled so they can be called first
+        for item in &program.items {
+            if let ProgramItem::Func(f) = item {
+                self.functions_map.insert(f.ident.clone(), f.clone());
+            }
+        }
         for item in program.items {
             match item {
-                ProgramItem::StaticVar(_s) => {
-                    todo!();
-                    // self.insert_variable(&s);
-                }
+                ProgramItem::StaticVar(_) | ProgramItem::Struct(_) | ProgramItem::Enum(_) => {}
                 ProgramItem::Func(f) => {
-                    self.functions_map.insert(f.ident.clone(), f.clone());
-                    self.function(f);
+                    self.function(f)?;
                 }
-                ProgramItem::Import(next_path, idents) => {
+                ProgramItem::Import(next_path, idents, _) => {
                     let mut new_path = String::new();
                     new_path.push_str(next_path.as_str());
                     new_path.push_str(".nmt");
@@ -321,117 +821,164 @@ impl Compiler {
             self.scoped_blocks.is_empty(),
             "Somting went wrong: Scope has not been cleared"
         );
-        Ok((self.instruct_buf.clone(), self.data_buf.clone()))
+        Ok((
+            self.instruct_buf.clone(),
+            self.data_buf.clone(),
+            self.bss_buf.clone(),
+        ))
     }
 
     /*
      *  keep in mind there could be a problem when a variable wants to access
      *  somthing that added after in code but it could be a feature too :)
      */
-    fn compile_block(&mut self, block: &Block) {
-        self.block_id += 1;
-        self.scoped_blocks.push(self.block_id);
+    fn compile_block(&mut self, block: &Block) -> Result<(), Diagnostic> {
+        self.enter_scope();
         for stmt in &block.stmts {
-            self.compile_stmt(stmt);
+            self.compile_stmt(stmt)?;
         }
+        self.exit_scope();
+        Ok(())
+    }
+
+    fn enter_scope(&mut self) {
+        self.block_id += 1;
+        self.scoped_blocks.push(self.block_id);
+    }
+
+    /// Drops the variables of the block since its id is reused by the next block
+    fn exit_scope(&mut self) {
+        let suffix = format!("%{}", self.block_id);
+        self.variables_map
+            .retain(|ident, _| !ident.ends_with(&suffix));
         self.block_id -= 1;
         self.scoped_blocks.pop().unwrap();
     }
 
-    fn compile_if_stmt(&mut self, ifs: &IFStmt, exit_tag: usize) {
-        self.compile_expr(&ifs.condition);
-        let next_tag = match ifs.else_block.as_ref() {
-            ElseBlock::None => exit_tag,
-            _ => self.instruct_buf.len(),
+    fn compile_if_stmt(&mut self, ifs: &IFStmt, exit_tag: usize) -> Result<(), Diagnostic> {
+        // The else label is made before the condition so it can not
+        // reuse the exit tag of the first if in the chain
+        let next_label = match ifs.else_block.as_ref() {
+            ElseBlock::None => format!(".L{exit_tag}"),
+            _ => format!(".L{}_else", self.instruct_buf.len()),
         };
-        self.instruct_buf.push(asm!("pop rax"));
-        self.instruct_buf.push(asm!("test rax, rax"));
-        self.instruct_buf.push(asm!("jz .L{}", next_tag));
+        self.compile_condition(&ifs.condition, false, &next_label)?;
 
-        self.compile_block(&ifs.then_block);
+        self.compile_block(&ifs.then_block)?;
         match ifs.else_block.as_ref() {
             ElseBlock::None => {
-                self.instruct_buf.push(asm!(".L{}:", next_tag));
+                self.instruct_buf.push(asm!("{next_label}:"));
             }
             ElseBlock::Else(b) => {
                 self.instruct_buf.push(asm!("jmp .L{}", exit_tag));
-                self.instruct_buf.push(asm!(".L{}:", next_tag));
-                self.compile_block(b);
+                self.instruct_buf.push(asm!("{next_label}:"));
+                self.compile_block(b)?;
                 self.instruct_buf.push(asm!(".L{}:", exit_tag));
             }
             ElseBlock::Elif(iff) => {
                 self.instruct_buf.push(asm!("jmp .L{}", exit_tag));
-                self.instruct_buf.push(asm!(".L{}:", next_tag));
-                self.compile_if_stmt(iff, exit_tag);
+                self.instruct_buf.push(asm!("{next_label}:"));
+                self.compile_if_stmt(iff, exit_tag)?;
             }
         }
+        Ok(())
     }
 
-    fn compile_stmt(&mut self, stmt: &Stmt) {
-        match stmt {
-            Stmt::VariableDecl(v) => {
-                self.insert_variable(v);
+    fn compile_stmt(&mut self, stmt: &Stmt) -> Result<(), Diagnostic> {
+        match &stmt.kind {
+            StmtKind::VariableDecl(v) => {
+                self.insert_variable(v)?;
             }
-            Stmt::Print(e) => {
-                self.compile_expr(e);
-                match e {
-                    Expr::String(_) => {
-                        self.instruct_buf.push(asm!("mov rax, 1"));
-                        self.instruct_buf.push(asm!("mov rdi, 1"));
-                        self.instruct_buf.push(asm!("pop rbx"));
-                        self.instruct_buf.push(asm!("pop rcx"));
-                        self.instruct_buf.push(asm!("mov rsi, rcx"));
-                        self.instruct_buf.push(asm!("mov rdx, rbx"));
-                        self.instruct_buf.push(asm!("syscall"));
-                    }
-                    _ => {
-                        self.instruct_buf.push(asm!("pop rdi"));
-                        self.instruct_buf.push(asm!("call print"));
-                    }
+            StmtKind::Print(p) => {
+                self.compile_print(p)?;
+            }
+            StmtKind::Panic(message) => {
+                self.compile_message_panic(message, &stmt.span)?;
+            }
+            StmtKind::Assert(a) => {
+                let tag = self.instruct_buf.len();
+                self.compile_condition(&a.condition, true, &format!(".L{tag}_assert"))?;
+                match &a.message {
+                    Some(message) => self.compile_message_panic(message, &stmt.span)?,
+                    None => self.compile_panic(&stmt.span, "assertion failed"),
                 }
+                self.instruct_buf.push(asm!(".L{tag}_assert:"));
             }
-            Stmt::If(ifs) => {
+            StmtKind::If(ifs) => {
                 let exit_tag = self.instruct_buf.len();
-                self.compile_if_stmt(ifs, exit_tag);
+                self.compile_if_stmt(ifs, exit_tag)?;
             }
-            Stmt::Assgin(a) => {
-                self.compile_assgin(a);
+            StmtKind::Assgin(a) => {
+                self.compile_assgin(a)?;
             }
-            Stmt::While(w) => {
-                self.compile_while(w);
+            StmtKind::While(w) => {
+                self.compile_while(w)?;
             }
-            Stmt::Expr(e) => match e {
-                Expr::FunctionCall(_) => {
-                    self.compile_expr(e);
+            StmtKind::Match(m) => {
+                self.compile_match(m)?;
+            }
+            StmtKind::For(f) => {
+                self.enter_scope();
+                let res = self.compile_for(f, &stmt.span);
+                self.exit_scope();
+                res?;
+            }
+            StmtKind::Expr(e) => match e.kind {
+                ExprKind::FunctionCall(_) => {
+                    self.compile_expr(e)?;
                 }
                 _ => {
-                    println!("Warning: Expretion with no effect ignored!");
+                    self.warning(&stmt.span, "Expretion with no effect ignored!");
                 }
             },
-            Stmt::Return(e) => {
-                self.compile_expr(e);
-                self.instruct_buf.push(asm!("pop rax"));
-                self.instruct_buf.push(asm!("leave"));
-                self.instruct_buf.push(asm!("ret"));
-                println!("Warning: might segfault add leave or fix dataframe");
+            StmtKind::Return(e) => {
+                if let Some(e) = e {
+                    self.compile_expr(e)?;
+                    let ret_type = self.ret_type.clone();
+                    if let Some(t) = &ret_type {
+                        self.compile_convert(&e.v_type, t);
+                    }
+                    if ret_type.as_ref().is_some_and(|t| t.is_view()) {
+                        self.instruct_buf.push(asm!("pop rdx"));
+                    }
+                    self.instruct_buf.push(asm!("pop rax"));
+                    // System V returns floats in xmm0
+                    if let Some(t) = ret_type.filter(|t| t.is_float()) {
+                        self.instruct_buf.push(to_xmm("xmm0", "a", &t));
+                    }
+                }
+                self.instruct_buf.push(asm!("jmp .Lreturn"));
             }
-            Stmt::InlineAsm(instructs) => {
+            StmtKind::InlineAsm(instructs) => {
                 for instr in instructs {
-                    self.compile_inline_asm(instr);
+                    self.compile_inline_asm(instr, &stmt.span)?;
                 }
             }
-            _ => {
-                todo!();
+            StmtKind::Break => {
+                let Some(tag) = self.loop_tags.last() else {
+                    return Err(
+                        Diagnostic::error("break outside of a loop").with_span(stmt.span.clone())
+                    );
+                };
+                self.instruct_buf.push(asm!("jmp .L{tag}_exit"));
+            }
+            StmtKind::Continue => {
+                let Some(tag) = self.loop_tags.last() else {
+                    return Err(Diagnostic::error("continue outside of a loop")
+                        .with_span(stmt.span.clone()));
+                };
+                self.instruct_buf.push(asm!("jmp .L{tag}"));
             }
         }
+        Ok(())
     }
 
-    fn compile_inline_asm(&mut self, instr: &String) {
+    fn compile_inline_asm(&mut self, instr: &String, span: &Span) -> Result<(), Diagnostic> {
         if instr.contains('%') {
             let mut final_instr = instr.clone();
             let chars = final_instr.chars().collect::<Vec<char>>();
             let mut index = 0;
-            let is_empty = |index: usize| (index >= chars.len());
+            let is_empty = |index: usize| index >= chars.len();
             while !is_empty(index) {
                 if chars[index] == '%' {
                     let mut ident = String::new();
@@ -444,15 +991,14 @@ impl Compiler {
                         index += 1;
                     }
                     if !ident.is_empty() {
-                        let v_map = self.find_variable(ident.clone()).unwrap_or_else(|| {
-                            eprintln!(
-                                "Error: Could not find variable {} in this scope",
-                                ident.clone()
-                            );
-                            exit(1);
-                        });
-                        let mem_acss =
-                            format!("{} [rbp-{}]", mem_word(8), v_map.offset + v_map.size);
+                        let v_map = self.find_variable(ident.clone()).ok_or_else(|| {
+                            Diagnostic::error(format!(
+                                "Could not find variable {} in this scope",
+                                ident
+                            ))
+                            .with_span(span.clone())
+                        })?;
+                        let mem_acss = format!("{} [{}]", mem_word(8), v_map.addr());
                         let mut temp = String::new();
                         temp.push_str(chars[0..(first_index)].iter().collect::<String>().as_str());
                         temp.push_str(mem_acss.as_str());
@@ -460,8 +1006,8 @@ impl Compiler {
                         final_instr = temp;
                         index += mem_acss.len()
                     } else {
-                        eprintln!("Error: Invalid Identifier for Inline Asm");
-                        exit(1);
+                        return Err(Diagnostic::error("Invalid Identifier for Inline Asm")
+                            .with_span(span.clone()));
                     }
                 } else {
                     index += 1;
@@ -471,181 +1017,939 @@ impl Compiler {
         } else {
             self.instruct_buf.push(asm!("{}", instr));
         }
+        Ok(())
+    }
+
+    fn compile_print(&mut self, p: &PrintStmt) -> Result<(), Diagnostic> {
+        self.compile_expr(&p.expr)?;
+        let newline = p.newline as usize;
+        if let Some(VariableType::String) = p.expr.v_type {
+            self.instruct_buf.push(asm!("mov rax, 1"));
+            self.instruct_buf.push(asm!("mov rdi, 1"));
+            self.instruct_buf.push(asm!("pop rbx"));
+            self.instruct_buf.push(asm!("pop rcx"));
+            self.instruct_buf.push(asm!("mov rsi, rcx"));
+            self.instruct_buf.push(asm!("mov rdx, rbx"));
+            self.instruct_buf.push(asm!("syscall"));
+            return Ok(());
+        }
+        if let Some(t) = p.expr.v_type.as_ref().filter(|t| t.is_float()) {
+            // __nmt_print_float takes the value as f64 in xmm0
+            self.compile_convert(&Some(t.clone()), &VariableType::F64);
+            self.instruct_buf.push(asm!("pop rax"));
+            self.instruct_buf.push(asm!("movq xmm0, rax"));
+            self.instruct_buf.push(asm!("mov rdx, {newline}"));
+            self.instruct_buf.push(asm!("call __nmt_print_float"));
+            return Ok(());
+        }
+        let format = p.format.unwrap_or(match p.expr.v_type {
+            Some(VariableType::Char) => PrintFormat::Char,
+            Some(VariableType::Pointer(_) | VariableType::String) => PrintFormat::Hex,
+            _ => PrintFormat::Dec,
+        });
+        self.instruct_buf.push(asm!("pop rdi"));
+        self.instruct_buf.push(asm!("mov rdx, {newline}"));
+        // __nmt_print_num takes the base in rsi and flags in rdx
+        // bit 0 is for the newline and bit 1 is for signed values
+        match format {
+            PrintFormat::Char => {
+                self.instruct_buf.push(asm!("call __nmt_print_char"));
+                return Ok(());
+            }
+            PrintFormat::Dec => {
+                if is_signed(&p.expr) {
+                    self.instruct_buf.push(asm!("or rdx, 2"));
+                }
+                self.instruct_buf.push(asm!("mov rsi, 10"));
+            }
+            PrintFormat::Hex => {
+                self.instruct_buf.push(asm!("mov rsi, 16"));
+            }
+            PrintFormat::Bin => {
+                self.instruct_buf.push(asm!("mov rsi, 2"));
+            }
+        }
+        self.instruct_buf.push(asm!("call __nmt_print_num"));
+        Ok(())
+    }
+
+    /// Declares a variable of a for loop in the current scope
+    fn loop_variable(
+        &mut self,
+        ident: &str,
+        v_type: VariableType,
+        init_value: Option<Expr>,
+        span: &Span,
+    ) -> Result<VariableMap, Diagnostic> {
+        self.insert_variable(&VariableDeclare {
+            mutable: true,
+            is_static: false,
+            ident: ident.to_string(),
+            v_type: Some(v_type),
+            init_value,
+            span: span.clone(),
+        })?;
+        self.get_vriable_map(&ident.to_string(), span)
     }
 
-    fn compile_while(&mut self, w_stmt: &WhileStmt) {
+    /// Lowers a for loop to the labels of a while loop
+    /// continue jumps to the increment and the first check is at .L{tag}_cond
+    fn compile_for(&mut self, f: &ForStmt, span: &Span) -> Result<(), Diagnostic> {
+        let v_type = f.v_type.clone().expect("resolved by checker");
+        // The range and the array are evaluated before the loop variable is declared
+        // Hidden variables can not be named in the code
+        // (var, counter, bound, jumps, step, base) where bound and step are a constant
+        // or a variable, jumps keep the loop running when it counts (up, down)
+        // and base is the address of the array
+        let (var, counter, bound, jumps, step, base) = match &f.iter {
+            ForIter::Range {
+                start,
+                end,
+                inclusive,
+                step,
+            } => {
+                let end =
+                    self.loop_variable("for.end", v_type.clone(), Some(*end.clone()), span)?;
+                let step = match step {
+                    None => Ok(1),
+                    Some(s) => match s.const_value() {
+                        Some(value) => Ok(value),
+                        None => {
+                            let step = self.loop_variable(
+                                "for.step",
+                                VariableType::Int,
+                                Some(*s.clone()),
+                                span,
+                            )?;
+                            // A step of zero would never reach the end
+                            let tag = self.instruct_buf.len();
+                            self.instruct_buf
+                                .push(asm!("cmp qword [{}], 0", step.addr()));
+                            self.instruct_buf.push(asm!("jne .L{tag}_step"));
+                            self.compile_panic(&s.span, "attempt to step a for loop by zero");
+                            self.instruct_buf.push(asm!(".L{tag}_step:"));
+                            Err(step)
+                        }
+                    },
+                };
+                let jump = |down| range_jump(down, *inclusive, v_type.is_signed());
+                let jumps = (jump(false), jump(true));
+                let var =
+                    self.loop_variable(&f.ident, v_type.clone(), Some(*start.clone()), span)?;
+                (var.clone(), var, Err(end), jumps, step, None)
+            }
+            ForIter::Array(array) if array.v_type.as_ref().is_some_and(|t| t.is_view()) => {
+                // The length of a string or a slice is only known at runtime
+                self.compile_expr(array)?;
+                let end = self.loop_variable("for.end", VariableType::Int, None, span)?;
+                self.instruct_buf.push(asm!("pop rax"));
+                self.instruct_buf
+                    .push(asm!("mov qword [{}], rax", end.addr()));
+                let base =
+                    self.loop_variable("for.array", VariableType::Pointer(None), None, span)?;
+                self.instruct_buf.push(asm!("pop rax"));
+                self.instruct_buf
+                    .push(asm!("mov qword [{}], rax", base.addr()));
+                let zero = Expr::new(ExprKind::Int(0), span.clone());
+                let index = self.loop_variable("for.index", VariableType::Int, Some(zero), span)?;
+                let var = self.loop_variable(&f.ident, v_type.clone(), None, span)?;
+                (var, index, Err(end), ("jl", "jg"), Ok(1), Some(base))
+            }
+            ForIter::Array(array) => {
+                let Some(VariableType::Array(_, len)) = array.v_type else {
+                    unreachable!("Array type is resolved by checker");
+                };
+                self.compile_addr(array)?;
+                let base =
+                    self.loop_variable("for.array", VariableType::Pointer(None), None, span)?;
+                self.instruct_buf.push(asm!("pop rax"));
+                self.instruct_buf
+                    .push(asm!("mov qword [{}], rax", base.addr()));
+                let zero = Expr::new(ExprKind::Int(0), span.clone());
+                let index = self.loop_variable("for.index", VariableType::Int, Some(zero), span)?;
+                let var = self.loop_variable(&f.ident, v_type.clone(), None, span)?;
+                (var, index, Ok(len as i64), ("jl", "jg"), Ok(1), Some(base))
+            }
+        };
+        let tag = self.instruct_buf.len();
+        let block_tag = tag + 1;
+        self.instruct_buf.push(asm!("jmp .L{tag}_cond"));
+        self.instruct_buf.push(asm!(".L{block_tag}:"));
+        if let Some(base) = &base {
+            // Copy the current item into the loop variable
+            let item_size = self.type_size(&v_type);
+            self.instruct_buf
+                .push(asm!("mov rax, qword [{}]", base.addr()));
+            self.instruct_buf
+                .push(asm!("mov rbx, qword [{}]", counter.addr()));
+            if item_size != 1 {
+                self.instruct_buf.push(asm!("imul rbx, {item_size}"));
+            }
+            self.instruct_buf.push(asm!("add rax, rbx"));
+            let mem_acss = format!("{} [rax]", mem_word(item_size));
+            self.instruct_buf
+                .push(load_rax(&mem_acss, item_size, v_type.is_signed()));
+            self.instruct_buf.push(asm!(
+                "mov {} [{}], {}",
+                mem_word(item_size),
+                var.addr(),
+                rbs("a", item_size)
+            ));
+        }
+        self.loop_tags.push(tag);
+        let res = self.compile_block(&f.block);
+        self.loop_tags.pop();
+        res?;
+        self.instruct_buf.push(asm!(".L{tag}:"));
+        let size = counter.item_size;
+        let counter_acss = format!("{} [{}]", mem_word(size), counter.addr());
+        match &step {
+            Ok(value) => {
+                self.instruct_buf.push(asm!("add {counter_acss}, {value}"));
+            }
+            Err(step) => {
+                self.instruct_buf
+                    .push(asm!("mov rax, qword [{}]", step.addr()));
+                self.instruct_buf
+                    .push(asm!("add {counter_acss}, {}", rbs("a", size)));
+            }
+        }
+        if base.is_none() {
+            // A step past the limits of the counter type ends the loop
+            // instead of wrapping around to a value that passes the compare
+            // Adding a negative step to an unsigned counter carries unless it wraps
+            match (&step, v_type.is_signed()) {
+                (_, true) => self.instruct_buf.push(asm!("jo .L{tag}_exit")),
+                (Ok(value), false) if *value < 0 => {
+                    self.instruct_buf.push(asm!("jnc .L{tag}_exit"));
+                }
+                (Ok(_), false) => self.instruct_buf.push(asm!("jc .L{tag}_exit")),
+                (Err(_), false) => {
+                    // rax still holds the step so it wrapped if the carry
+                    // does not match the sign of the step
+                    self.instruct_buf.push(asm!("setc cl"));
+                    self.instruct_buf.push(asm!("test rax, rax"));
+                    self.instruct_buf.push(asm!("sets dl"));
+                    self.instruct_buf.push(asm!("cmp cl, dl"));
+                    self.instruct_buf.push(asm!("jne .L{tag}_exit"));
+                }
+            }
+        }
+        self.instruct_buf.push(asm!(".L{tag}_cond:"));
+        match bound {
+            Ok(value) => {
+                self.instruct_buf.push(asm!("mov rbx, {value}"));
+            }
+            Err(end) => {
+                let end_acss = format!("{} [{}]", mem_word(size), end.addr());
+                self.instruct_buf
+                    .push(load_rax(&end_acss, size, v_type.is_signed()));
+                self.instruct_buf.push(asm!("mov rbx, rax"));
+            }
+        }
+        self.instruct_buf
+            .push(load_rax(&counter_acss, size, v_type.is_signed()));
+        match &step {
+            Ok(value) => {
+                let jump = if *value < 0 { jumps.1 } else { jumps.0 };
+                self.instruct_buf.push(asm!("cmp rax, rbx"));
+                self.instruct_buf.push(asm!("{jump} .L{block_tag}"));
+            }
+            Err(step) => {
+                // The sign of a step known at runtime picks the direction
+                self.instruct_buf
+                    .push(asm!("cmp qword [{}], 0", step.addr()));
+                self.instruct_buf.push(asm!("jl .L{tag}_down"));
+                self.instruct_buf.push(asm!("cmp rax, rbx"));
+                self.instruct_buf.push(asm!("{} .L{block_tag}", jumps.0));
+                self.instruct_buf.push(asm!("jmp .L{tag}_exit"));
+                self.instruct_buf.push(asm!(".L{tag}_down:"));
+                self.instruct_buf.push(asm!("cmp rax, rbx"));
+                self.instruct_buf.push(asm!("{} .L{block_tag}", jumps.1));
+            }
+        }
+        self.instruct_buf.push(asm!(".L{tag}_exit:"));
+        Ok(())
+    }
+
+    fn compile_match(&mut self, m: &MatchStmt) -> Result<(), Diagnostic> {
+        let tag = self.instruct_buf.len();
+        self.compile_expr(&m.expr)?;
+        self.instruct_buf.push(asm!("pop rax"));
+        // (first value, last value, arm index)
+        let mut cases = Vec::<(i64, i64, usize)>::new();
+        let mut default = None;
+        for (index, arm) in m.arms.iter().enumerate() {
+            for pattern in &arm.patterns {
+                let value = |e: &Expr| e.const_value().expect("resolved by checker");
+                match pattern {
+                    Pattern::Default => default = Some(index),
+                    Pattern::Value(v) => cases.push((value(v), value(v), index)),
+                    Pattern::Range {
+                        start,
+                        end,
+                        inclusive,
+                    } => {
+                        // An exclusive range ending at i64::MIN is empty and
+                        // rejected by the checker
+                        let last = match inclusive {
+                            true => value(end),
+                            false => value(end).checked_sub(1).expect("checked by checker"),
+                        };
+                        cases.push((value(start), last, index));
+                    }
+                }
+            }
+        }
+        let arm_label = |arm: Option<usize>| match arm {
+            Some(index) => format!(".L{tag}_arm{index}"),
+            None => format!(".L{tag}_exit"),
+        };
+        let default_label = arm_label(default);
+        if let Some((min, max)) = Self::jump_table_range(&cases) {
+            self.instruct_buf.push(asm!("mov rbx, {min}"));
+            self.instruct_buf.push(asm!("sub rax, rbx"));
+            self.instruct_buf.push(asm!("mov rbx, {}", max - min));
+            self.instruct_buf.push(asm!("cmp rax, rbx"));
+            self.instruct_buf.push(asm!("ja {default_label}"));
+            self.instruct_buf.push(asm!("jmp [.L{tag}_table+rax*8]"));
+            self.instruct_buf.push(asm!(".L{tag}_table:"));
+            for value in min..=max {
+                // The first arm that matches a value is taken
+                let arm = cases
+                    .iter()
+                    .find(|c| c.0 <= value && value <= c.1)
+                    .map(|c| c.2)
+                    .or(default);
+                self.instruct_buf.push(asm!("dq {}", arm_label(arm)));
+            }
+        } else {
+            let unsigned = !is_signed(&m.expr);
+            let below = condition_code(&CompareOp::Smaller, unsigned);
+            let below_eq = condition_code(&CompareOp::SmallerEq, unsigned);
+            for (index, (first, last, arm)) in cases.iter().enumerate() {
+                self.instruct_buf.push(asm!("mov rbx, {first}"));
+                self.instruct_buf.push(asm!("cmp rax, rbx"));
+                if first == last {
+                    self.instruct_buf.push(asm!("je {}", arm_label(Some(*arm))));
+                    continue;
+                }
+                self.instruct_buf.push(asm!("j{below} .L{tag}_case{index}"));
+                self.instruct_buf.push(asm!("mov rbx, {last}"));
+                self.instruct_buf.push(asm!("cmp rax, rbx"));
+                self.instruct_buf
+                    .push(asm!("j{below_eq} {}", arm_label(Some(*arm))));
+                self.instruct_buf.push(asm!(".L{tag}_case{index}:"));
+            }
+            self.instruct_buf.push(asm!("jmp {default_label}"));
+        }
+        for (index, arm) in m.arms.iter().enumerate() {
+            self.instruct_buf.push(asm!(".L{tag}_arm{index}:"));
+            self.compile_block(&arm.block)?;
+            self.instruct_buf.push(asm!("jmp .L{tag}_exit"));
+        }
+        self.instruct_buf.push(asm!(".L{tag}_exit:"));
+        Ok(())
+    }
+
+    /// Returns the range of values of a jump table if the cases are dense enough
+    fn jump_table_range(cases: &[(i64, i64, usize)]) -> Option<(i64, i64)> {
+        let min = cases.iter().map(|c| c.0).min()?;
+        let max = cases.iter().map(|c| c.1).max()?;
+        // Ranges can cover every i64 so the sizes are counted in i128
+        let count: i128 = cases.iter().map(|c| c.1 as i128 - c.0 as i128 + 1).sum();
+        let size = max as i128 - min as i128 + 1;
+        (count >= 4 && size <= 2 * count && size <= 1024).then_some((min, max))
+    }
+
+    fn compile_while(&mut self, w_stmt: &WhileStmt) -> Result<(), Diagnostic> {
         let cond_tag = self.instruct_buf.len();
         self.instruct_buf.push(asm!("jmp .L{}", cond_tag));
         let block_tag = cond_tag + 1;
         self.instruct_buf.push(asm!(".L{}:", block_tag));
-        self.compile_block(&w_stmt.block);
+        // break jumps to .L{cond_tag}_exit and continue to the condition
+        self.loop_tags.push(cond_tag);
+        let res = self.compile_block(&w_stmt.block);
+        self.loop_tags.pop();
+        res?;
         self.instruct_buf.push(asm!(".L{}:", cond_tag));
-        // Jump after a compare
-        self.compile_expr(&w_stmt.condition);
+        self.compile_condition(&w_stmt.condition, true, &format!(".L{block_tag}"))?;
+        self.instruct_buf.push(asm!(".L{}_exit:", cond_tag));
+        Ok(())
+    }
+
+    /// Jumps to `label` when the condition is `jump_if` and falls through otherwise
+    /// The right side of && and || is skipped when the left side decides the result
+    fn compile_condition(
+        &mut self,
+        cond: &Expr,
+        jump_if: bool,
+        label: &str,
+    ) -> Result<(), Diagnostic> {
+        match &cond.kind {
+            ExprKind::Not(e) => self.compile_condition(e, !jump_if, label)?,
+            ExprKind::Logical(l) => {
+                // jump_if matches the value that decides the result on its own
+                let decides = matches!(l.op, LogicalOp::Or);
+                if jump_if == decides {
+                    self.compile_condition(&l.left, jump_if, label)?;
+                    self.compile_condition(&l.right, jump_if, label)?;
+                } else {
+                    let skip = format!(".Llogic{}", self.logic_tags);
+                    self.logic_tags += 1;
+                    self.compile_condition(&l.left, decides, &skip)?;
+                    self.compile_condition(&l.right, jump_if, label)?;
+                    self.instruct_buf.push(asm!("{skip}:"));
+                }
+            }
+            ExprKind::Compare(c) => {
+                let cc = self.compile_compare(c, !jump_if)?;
+                self.instruct_buf.push(asm!("j{cc} {label}"));
+            }
+            _ => {
+                self.compile_expr(cond)?;
+                self.instruct_buf.push(asm!("pop rax"));
+                self.instruct_buf.push(asm!("test rax, rax"));
+                let cc = if jump_if { "nz" } else { "z" };
+                self.instruct_buf.push(asm!("j{cc} {label}"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Compares the two sides and sets the flags
+    /// Returns the condition code that holds when the compare is true
+    /// or when it is false if `negate` is set
+    fn compile_compare(
+        &mut self,
+        c: &CompareExpr,
+        negate: bool,
+    ) -> Result<&'static str, Diagnostic> {
+        let op = if negate { c.op.negated() } else { c.op.clone() };
+        if let Some(VariableType::String) = c.left.v_type {
+            self.compile_expr(c.left.as_ref())?;
+            self.compile_expr(c.right.as_ref())?;
+            self.instruct_buf.push(asm!("pop rcx"));
+            self.instruct_buf.push(asm!("pop rdx"));
+            self.instruct_buf.push(asm!("pop rsi"));
+            self.instruct_buf.push(asm!("pop rdi"));
+            self.instruct_buf.push(asm!("call __nmt_str_cmp"));
+            // __nmt_str_cmp returns the order of the strings as -1, 0 or 1
+            self.instruct_buf.push(asm!("cmp rax, 0"));
+            return Ok(condition_code(&op, false));
+        }
+        // Both sides are converted to their common type
+        let common = match (&c.left.v_type, &c.right.v_type) {
+            (Some(l), Some(r))
+                if (l.is_numeric() || l.is_float()) && (r.is_numeric() || r.is_float()) =>
+            {
+                Some(l.arith_type(r))
+            }
+            _ => None,
+        };
+        self.compile_expr(c.left.as_ref())?;
+        if let Some(t) = &common {
+            self.compile_convert(&c.left.v_type, t);
+        }
+        self.compile_expr(c.right.as_ref())?;
+        if let Some(t) = &common {
+            self.compile_convert(&c.right.v_type, t);
+        }
+        self.instruct_buf.push(asm!("pop rbx"));
         self.instruct_buf.push(asm!("pop rax"));
-        self.instruct_buf.push(asm!("test rax, rax"));
-        self.instruct_buf.push(asm!("jnz .L{}", block_tag));
-    }
-
-    fn assgin_op(&mut self, op: &AssginOp, v_map: &VariableMap) {
-        let mem_acss = if v_map.item_size != v_map.size {
-            format!(
-                "{} [rbp-{}+rbx*{}]",
-                mem_word(v_map.item_size),
-                v_map.offset + v_map.size,
-                v_map.item_size
-            )
+        let Some(t) = common.filter(|t| t.is_float()) else {
+            self.instruct_buf.push(asm!("cmp rax, rbx"));
+            return Ok(condition_code(&op, is_unsigned_compare(c)));
+        };
+        // ucomis sets the flags like an unsigned compare and sets ZF, PF and CF
+        // when a side is NaN, < and <= swap the sides to use the above codes
+        // that are false for NaN and == and != also check PF
+        self.instruct_buf.push(to_xmm("xmm0", "a", &t));
+        self.instruct_buf.push(to_xmm("xmm1", "b", &t));
+        let (left, right, float_op) = match c.op {
+            CompareOp::Smaller => ("xmm1", "xmm0", CompareOp::Bigger),
+            CompareOp::SmallerEq => ("xmm1", "xmm0", CompareOp::BiggerEq),
+            ref op => ("xmm0", "xmm1", op.clone()),
+        };
+        self.instruct_buf
+            .push(asm!("ucomi{} {left}, {right}", float_suffix(&t)));
+        let cc = condition_code(&float_op, true);
+        self.instruct_buf.push(asm!("set{cc} al"));
+        match float_op {
+            CompareOp::Eq => {
+                self.instruct_buf.push(asm!("setnp cl"));
+                self.instruct_buf.push(asm!("and al, cl"));
+            }
+            CompareOp::NotEq => {
+                self.instruct_buf.push(asm!("setp cl"));
+                self.instruct_buf.push(asm!("or al, cl"));
+            }
+            _ => {}
+        }
+        self.instruct_buf.push(asm!("test al, al"));
+        Ok(if negate { "z" } else { "nz" })
+    }
+
+    /// Divides rax by rbx leaving the quotient in rax and the remainder in rdx
+    fn compile_div(&mut self, signed: bool) {
+        if signed {
+            self.instruct_buf.push(asm!("cqo"));
+            self.instruct_buf.push(asm!("idiv rbx"));
         } else {
-            format!(
-                "{} [rbp-{}]",
-                mem_word(v_map.item_size),
-                v_map.offset + v_map.size
-            )
+            self.instruct_buf.push(asm!("xor rdx, rdx"));
+            self.instruct_buf.push(asm!("div rbx"));
+        }
+    }
+
+    /// Multiplies rax by rbx
+    /// Unsigned 64 bit products use mul so the carry flag tells the overflow
+    fn compile_mul(&mut self, t: &Option<VariableType>) {
+        if self.options.overflow_checks && *t == Some(VariableType::UInt) {
+            self.instruct_buf.push(asm!("mul rbx"));
+        } else {
+            self.instruct_buf.push(asm!("imul rax, rbx"));
+        }
+    }
+
+    /// Panics if the result in rax overflowed its integer type
+    /// 64 bit results are checked by the flags of the last instruction
+    /// and smaller ones by extending their low bits back to 64 bits
+    fn compile_overflow_check(&mut self, t: &Option<VariableType>, span: &Span, op: &str) {
+        if !self.options.overflow_checks {
+            return;
+        }
+        let Some(t) = t else {
+            return;
+        };
+        let Some(size) = t.int_size().filter(|_| *t != VariableType::Bool) else {
+            return;
+        };
+        let tag = self.instruct_buf.len();
+        match (size, t.is_signed()) {
+            (8, true) => self.instruct_buf.push(asm!("jno .L{tag}_nooverflow")),
+            (8, false) => self.instruct_buf.push(asm!("jnc .L{tag}_nooverflow")),
+            (_, signed) => {
+                self.instruct_buf.push(asm!("mov rdx, rax"));
+                self.instruct_buf.push(extend_rax(size, signed));
+                self.instruct_buf.push(asm!("cmp rax, rdx"));
+                self.instruct_buf.push(asm!("je .L{tag}_nooverflow"));
+            }
+        }
+        self.compile_panic(span, &format!("attempt to {op} with overflow"));
+        self.instruct_buf.push(asm!(".L{tag}_nooverflow:"));
+    }
+
+    /// Wraps the result in rax to the size of its integer type
+    /// 64 bit results already wrap in the register
+    fn compile_truncate(&mut self, t: &Option<VariableType>) {
+        let Some(t) = t.as_ref().filter(|t| **t != VariableType::Bool) else {
+            return;
+        };
+        if let Some(size) = t.int_size().filter(|size| *size < 8) {
+            self.instruct_buf.push(extend_rax(size, t.is_signed()));
+        }
+    }
+
+    /// Panics if the divisor in rbx is zero
+    /// or if a signed division of the smallest value by -1 overflows
+    fn compile_divisor_check(&mut self, t: &Option<VariableType>, span: &Span, op: &Op) {
+        if !self.options.overflow_checks {
+            return;
+        }
+        let Some(t) = t.as_ref().filter(|t| t.int_size().is_some()) else {
+            return;
+        };
+        let (op, zero_message) = match op {
+            Op::Devide => ("divide", "attempt to divide by zero"),
+            _ => (
+                "calculate the remainder",
+                "attempt to calculate the remainder with a divisor of zero",
+            ),
         };
-        let reg = rbs("a", v_map.item_size);
+        let tag = self.instruct_buf.len();
+        self.instruct_buf.push(asm!("test rbx, rbx"));
+        self.instruct_buf.push(asm!("jnz .L{tag}_nonzero"));
+        self.compile_panic(span, zero_message);
+        self.instruct_buf.push(asm!(".L{tag}_nonzero:"));
+        if let (Some(size), true) = (t.int_size(), t.is_signed()) {
+            let min = i64::MIN >> (64 - size * 8);
+            self.instruct_buf.push(asm!("cmp rbx, -1"));
+            self.instruct_buf.push(asm!("jne .L{tag}_nooverflow"));
+            self.instruct_buf.push(asm!("mov rdx, {min}"));
+            self.instruct_buf.push(asm!("cmp rax, rdx"));
+            self.instruct_buf.push(asm!("jne .L{tag}_nooverflow"));
+            self.compile_panic(span, &format!("attempt to {op} with overflow"));
+            self.instruct_buf.push(asm!(".L{tag}_nooverflow:"));
+        }
+    }
+
+    /// Applies the assignment to the memory operand
+    /// The value is on top of the stack and `scale` is the size of the
+    /// pointee when the target is a typed pointer
+    fn assgin_op(
+        &mut self,
+        op: &AssginOp,
+        mem_acss: &str,
+        size: usize,
+        scale: usize,
+        target: &VariableType,
+        span: &Span,
+    ) {
+        let reg = rbs("a", size);
+        let signed = target.is_signed();
         self.instruct_buf.push(asm!("pop rax"));
+        if target.is_float() && *op != AssginOp::Eq {
+            let instr = match op {
+                AssginOp::PlusEq => "add",
+                AssginOp::SubEq => "sub",
+                AssginOp::MultiEq => "mul",
+                AssginOp::DevideEq => "div",
+                _ => unreachable!("Checked by the checker"),
+            };
+            self.instruct_buf.push(to_xmm("xmm1", "a", target));
+            self.instruct_buf.push(load_rax(mem_acss, size, false));
+            self.instruct_buf.push(to_xmm("xmm0", "a", target));
+            self.instruct_buf
+                .push(asm!("{instr}{} xmm0, xmm1", float_suffix(target)));
+            self.instruct_buf.push(from_xmm("a", "xmm0", target));
+            self.instruct_buf.push(asm!("mov {mem_acss},{reg}"));
+            return;
+        }
         match op {
             AssginOp::Eq => {
                 self.instruct_buf.push(asm!("mov {mem_acss},{reg}"));
             }
-            AssginOp::PlusEq => {
-                self.instruct_buf.push(asm!("add {mem_acss},{reg}"));
-            }
-            AssginOp::SubEq => {
-                self.instruct_buf.push(asm!("sub {mem_acss},{reg}"));
+            AssginOp::PlusEq | AssginOp::SubEq => {
+                if scale != 1 {
+                    self.instruct_buf.push(asm!("imul rax, {scale}"));
+                }
+                let (instr, name) = if *op == AssginOp::PlusEq {
+                    ("add", "add")
+                } else {
+                    ("sub", "subtract")
+                };
+                if self.options.overflow_checks && target.int_size().is_some() {
+                    // Done in 64 bits so smaller types can be checked
+                    self.instruct_buf.push(asm!("mov rbx, rax"));
+                    self.instruct_buf.push(load_rax(mem_acss, size, signed));
+                    self.instruct_buf.push(asm!("{instr} rax, rbx"));
+                    self.compile_overflow_check(&Some(target.clone()), span, name);
+                    self.instruct_buf.push(asm!("mov {mem_acss},{reg}"));
+                } else {
+                    self.instruct_buf.push(asm!("{instr} {mem_acss},{reg}"));
+                }
             }
             AssginOp::MultiEq => {
-                self.instruct_buf.push(asm!("imul {mem_acss},{reg}"));
-            }
-            AssginOp::DevideEq => {
-                // self.instruct_buf.push(asm!("cdq"));
-                self.instruct_buf.push(asm!("idiv rbx"));
+                let target = Some(target.clone());
+                self.instruct_buf.push(asm!("mov rbx, rax"));
+                self.instruct_buf.push(load_rax(mem_acss, size, signed));
+                self.compile_mul(&target);
+                self.compile_overflow_check(&target, span, "multiply");
                 self.instruct_buf.push(asm!("mov {mem_acss},{reg}"));
             }
-            AssginOp::ModEq => {
-                self.instruct_buf.push(asm!("cdq"));
-                self.instruct_buf.push(asm!("idiv rbx"));
+            AssginOp::DevideEq | AssginOp::ModEq => {
+                let div_op = if *op == AssginOp::DevideEq {
+                    Op::Devide
+                } else {
+                    Op::Mod
+                };
+                self.instruct_buf.push(asm!("mov rbx, rax"));
+                self.instruct_buf.push(load_rax(mem_acss, size, signed));
+                self.compile_divisor_check(&Some(target.clone()), span, &div_op);
+                self.compile_div(signed);
+                let reg = if *op == AssginOp::DevideEq {
+                    reg
+                } else {
+                    rbs("d", size)
+                };
                 self.instruct_buf.push(asm!("mov {mem_acss},{reg}"));
             }
         }
     }
 
-    fn compile_assgin(&mut self, assign: &Assgin) {
-        match &assign.left {
-            Expr::Variable(v) => {
-                let v_map = self.get_vriable_map(v);
+    fn compile_assgin(&mut self, assign: &Assgin) -> Result<(), Diagnostic> {
+        let scale = match &assign.left.v_type {
+            Some(VariableType::Pointer(t)) => self.pointee_size(t),
+            _ => 1,
+        };
+        let target = assign.left.v_type.clone().expect("resolved by checker");
+        match &assign.left.kind {
+            ExprKind::Variable(v) => {
+                let v_map = self.get_vriable_map(v, &assign.left.span)?;
                 if !v_map.is_mut {
-                    eprintln!("Error: Variable is not mutable. Did you forgot to define it with '=' insted of ':=' ?");
-                    exit(1);
+                    return Err(Diagnostic::error(format!("Variable ({v}) is not mutable"))
+                        .with_span(assign.left.span.clone())
+                        .with_note("Did you forgot to define it with '=' insted of ':=' ?"));
+                }
+                self.compile_expr(&assign.right)?;
+                self.compile_convert(&assign.right.v_type, &target);
+                if target.is_view() {
+                    self.store_view(&v_map.addr());
+                    return Ok(());
                 }
-                self.compile_expr(&assign.right);
-                self.assgin_op(&assign.op, &v_map);
+                let mem_acss = format!("{} [{}]", mem_word(v_map.item_size), v_map.addr());
+                let span = &assign.left.span;
+                self.assgin_op(&assign.op, &mem_acss, v_map.item_size, scale, &target, span);
             }
-            Expr::ArrayIndex(ai) => {
-                let v_map = self.get_vriable_map(&ai.ident);
-                if !v_map.is_mut {
-                    eprintln!("Error: Variable is not mutable. Did you forgot to define it with '=' insted of ':=' ?");
-                    exit(1);
+            _ => {
+                self.compile_expr(&assign.right)?;
+                self.compile_convert(&assign.right.v_type, &target);
+                self.compile_addr(&assign.left)?;
+                if target.is_view() {
+                    self.instruct_buf.push(asm!("pop rdx"));
+                    self.store_view("rdx");
+                    return Ok(());
+                }
+                self.instruct_buf.push(asm!("pop rcx"));
+                let size = self.value_size(&assign.left);
+                let mem_acss = format!("{} [rcx]", mem_word(size));
+                let span = &assign.left.span;
+                self.assgin_op(&assign.op, &mem_acss, size, scale, &target, span);
+            }
+        }
+        Ok(())
+    }
+
+    /// Size of the value of a checked expression
+    fn value_size(&self, expr: &Expr) -> usize {
+        expr.v_type.as_ref().map_or(8, |t| self.type_size(t))
+    }
+
+    /// Size of the items a pointer points to
+    /// Untyped pointers point to bytes
+    fn pointee_size(&self, pointee: &Option<Box<VariableType>>) -> usize {
+        pointee.as_ref().map_or(1, |t| self.type_size(t))
+    }
+
+    /// Pushes the address of an lvalue
+    fn compile_addr(&mut self, expr: &Expr) -> Result<(), Diagnostic> {
+        match &expr.kind {
+            ExprKind::Variable(v) => {
+                let v_map = self.get_vriable_map(v, &expr.span)?;
+                self.instruct_buf.push(asm!("lea rax, [{}]", v_map.addr()));
+                self.instruct_buf.push(asm!("push rax"));
+            }
+            ExprKind::ArrayIndex(ai) => {
+                // Arrays are indexed in place and pointers by their value
+                match ai.base.v_type {
+                    Some(VariableType::Array(_, _)) => self.compile_addr(&ai.base)?,
+                    // Strings and slices push their length after the address
+                    _ => self.compile_expr(&ai.base)?,
                 }
-                self.compile_expr(&assign.right);
-                self.compile_expr(&ai.indexer);
+                self.compile_expr(&ai.indexer)?;
                 self.instruct_buf.push(asm!("pop rbx"));
-                self.assgin_op(&assign.op, &v_map);
+                let bound = match &ai.base.v_type {
+                    Some(VariableType::Array(_, len)) => Some(len.to_string()),
+                    Some(t) if t.is_view() => {
+                        self.instruct_buf.push(asm!("pop rcx"));
+                        Some("rcx".to_string())
+                    }
+                    _ => None,
+                };
+                self.instruct_buf.push(asm!("pop rax"));
+                if let Some(bound) = bound.filter(|_| self.options.bounds_checks) {
+                    // Negative indices are out of bounds as unsigned numbers
+                    let tag = self.instruct_buf.len();
+                    self.instruct_buf.push(asm!("cmp rbx, {bound}"));
+                    self.instruct_buf.push(asm!("jb .L{tag}_inbounds"));
+                    self.compile_panic(&expr.span, "index out of bounds");
+                    self.instruct_buf.push(asm!(".L{tag}_inbounds:"));
+                }
+                let item_size = self.value_size(expr);
+                if item_size != 1 {
+                    self.instruct_buf.push(asm!("imul rbx, {item_size}"));
+                }
+                self.instruct_buf.push(asm!("add rax, rbx"));
+                self.instruct_buf.push(asm!("push rax"));
+            }
+            ExprKind::Deref(e) => {
+                self.compile_expr(e)?;
+            }
+            ExprKind::Field(fa) => {
+                self.compile_field_addr(fa)?;
             }
             _ => {
-                eprintln!("Error: Expected a Variable type expression found Value");
-                exit(1);
+                return Err(Diagnostic::error("Can not take the address of a value")
+                    .with_span(expr.span.clone()));
             }
         }
+        Ok(())
     }
 
-    fn get_vriable_map(&mut self, var_ident: &String) -> VariableMap {
-        self.find_variable(var_ident.clone()).unwrap_or_else(|| {
-            eprintln!("Error: Trying to access an Undifined variable ({var_ident})");
-            exit(1);
+    fn compile_field_addr(&mut self, fa: &FieldAccess) -> Result<(), Diagnostic> {
+        // Fields of a struct pointer are accessed through the pointer
+        let ident = match &fa.base.v_type {
+            Some(VariableType::Struct(ident)) => {
+                self.compile_addr(&fa.base)?;
+                ident
+            }
+            Some(VariableType::Pointer(Some(t))) => {
+                self.compile_expr(&fa.base)?;
+                let VariableType::Struct(ident) = t.as_ref() else {
+                    unreachable!("Field access on a non struct pointer");
+                };
+                ident
+            }
+            _ => unreachable!("Field access type is resolved by the checker"),
+        };
+        let (offset, _) = self.struct_layout(ident, Some(&fa.field));
+        if offset != 0 {
+            self.instruct_buf.push(asm!("pop rax"));
+            self.instruct_buf.push(asm!("add rax, {offset}"));
+            self.instruct_buf.push(asm!("push rax"));
+        }
+        Ok(())
+    }
+
+    /// Pushes the value stored at the address on top of the stack
+    fn compile_load(&mut self, expr: &Expr) {
+        // Arrays and structs are used through their address
+        if let Some(VariableType::Array(_, _) | VariableType::Struct(_)) = expr.v_type {
+            return;
+        }
+        if expr.v_type.as_ref().is_some_and(|t| t.is_view()) {
+            self.instruct_buf.push(asm!("pop rax"));
+            self.instruct_buf.push(asm!("push qword [rax]"));
+            self.instruct_buf.push(asm!("push qword [rax+8]"));
+            return;
+        }
+        let size = self.value_size(expr);
+        self.instruct_buf.push(asm!("pop rax"));
+        self.instruct_buf.push(load_rax(
+            &format!("{} [rax]", mem_word(size)),
+            size,
+            is_signed(expr),
+        ));
+        self.instruct_buf.push(asm!("push rax"));
+    }
+
+    fn get_vriable_map(
+        &mut self,
+        var_ident: &String,
+        span: &Span,
+    ) -> Result<VariableMap, Diagnostic> {
+        self.find_variable(var_ident.clone()).ok_or_else(|| {
+            Diagnostic::error(format!(
+                "Trying to access an Undifined variable ({var_ident})"
+            ))
+            .with_span(span.clone())
         })
     }
 
-    fn compile_expr(&mut self, expr: &Expr) {
+    fn compile_expr(&mut self, expr: &Expr) -> Result<(), Diagnostic> {
         // left = compile expr
         // right = compile expr
         // +
-        match expr {
-            Expr::Variable(v) => {
-                let v_map = self.get_vriable_map(v);
-                let mem_acss = format!(
-                    "{} [rbp-{}]",
-                    mem_word(v_map.item_size),
-                    v_map.offset + v_map.size
-                );
+        match &expr.kind {
+            ExprKind::Variable(_) if expr.v_type.as_ref().is_some_and(|t| t.is_view()) => {
+                self.compile_addr(expr)?;
+                self.compile_load(expr);
+            }
+            ExprKind::Variable(v) => {
+                let v_map = self.get_vriable_map(v, &expr.span)?;
+                let mem_acss = format!("{} [{}]", mem_word(v_map.item_size), v_map.addr());
                 self.instruct_buf
-                    .push(asm!("mov {},{mem_acss}", rbs("a", v_map.item_size)));
+                    .push(load_rax(&mem_acss, v_map.item_size, is_signed(expr)));
                 self.instruct_buf.push(asm!("push rax"));
             }
-            Expr::Char(x) => {
+            ExprKind::Char(x) => {
                 self.instruct_buf.push(asm!("push {x}"));
             }
-            Expr::Int(x) => {
-                // push x
-                self.instruct_buf.push(asm!("push {x}"));
+            ExprKind::Int(x) => {
+                // push only takes a 32 bit immediate
+                if i32::try_from(*x).is_ok() {
+                    self.instruct_buf.push(asm!("push {x}"));
+                } else {
+                    self.instruct_buf.push(asm!("mov rax, {x}"));
+                    self.instruct_buf.push(asm!("push rax"));
+                }
+            }
+            ExprKind::Float(x) => {
+                let id = self.data_buf.len();
+                self.data_buf.push(asm!("float{id} dq {:#x}", x.to_bits()));
+                self.instruct_buf.push(asm!("mov rax, qword [float{id}]"));
+                self.instruct_buf.push(asm!("push rax"));
+            }
+            ExprKind::EnumVariant(ev) => {
+                let value = ev.value.expect("resolved by checker");
+                self.instruct_buf.push(asm!("mov rax, {value}"));
+                self.instruct_buf.push(asm!("push rax"));
             }
-            Expr::Compare(c) => {
-                // TODO: Convert exprs to 0 or 1 and push into stack
-                self.compile_expr(c.left.as_ref());
-                self.compile_expr(c.right.as_ref());
+            ExprKind::Compare(c) => {
+                let cc = self.compile_compare(c, false)?;
                 self.instruct_buf.push(asm!("mov rcx, 0"));
                 self.instruct_buf.push(asm!("mov rdx, 1"));
+                self.instruct_buf.push(asm!("cmov{cc} rcx, rdx"));
+                self.instruct_buf.push(asm!("push rcx"));
+            }
+            ExprKind::Logical(_) | ExprKind::Not(_) => {
+                let tag = self.logic_tags;
+                self.logic_tags += 1;
+                self.compile_condition(expr, false, &format!(".Llogic{tag}_false"))?;
+                self.instruct_buf.push(asm!("push 1"));
+                self.instruct_buf.push(asm!("jmp .Llogic{tag}_exit"));
+                self.instruct_buf.push(asm!(".Llogic{tag}_false:"));
+                self.instruct_buf.push(asm!("push 0"));
+                self.instruct_buf.push(asm!(".Llogic{tag}_exit:"));
+            }
+            ExprKind::Binary(b) => {
+                if let Some(t) = expr.v_type.as_ref().filter(|t| t.is_float()) {
+                    return self.compile_float_binary(b, t);
+                }
+                self.compile_expr(b.left.as_ref())?;
+                self.compile_expr(b.right.as_ref())?;
                 self.instruct_buf.push(asm!("pop rbx"));
                 self.instruct_buf.push(asm!("pop rax"));
-                self.instruct_buf.push(asm!("cmp rax, rbx"));
-                match c.op {
-                    CompareOp::Eq => {
-                        self.instruct_buf.push(asm!("cmove rcx, rdx"));
-                    }
-                    CompareOp::NotEq => {
-                        self.instruct_buf.push(asm!("cmovne rcx, rdx"));
-                    }
-                    CompareOp::Bigger => {
-                        self.instruct_buf.push(asm!("cmovg rcx, rdx"));
-                    }
-                    CompareOp::Smaller => {
-                        self.instruct_buf.push(asm!("cmovl rcx, rdx"));
+                let pointee_size = |e: &Expr| match &e.v_type {
+                    Some(VariableType::Pointer(t)) => Some(self.pointee_size(t)),
+                    _ => None,
+                };
+                // Pointer arithmetic is scaled by the size of the pointee
+                let scales = (pointee_size(&b.left), pointee_size(&b.right));
+                match scales {
+                    (Some(size), None) if size != 1 => {
+                        self.instruct_buf.push(asm!("imul rbx, {size}"));
                     }
-                    CompareOp::BiggerEq => {
-                        self.instruct_buf.push(asm!("cmovge rcx, rdx"));
+                    (None, Some(size)) if size != 1 => {
+                        self.instruct_buf.push(asm!("imul rax, {size}"));
                     }
-                    CompareOp::SmallerEq => {
-                        self.instruct_buf.push(asm!("cmovle rcx, rdx"));
+                    (Some(size), Some(_)) if size != 1 => {
+                        self.instruct_buf.push(asm!("sub rax, rbx"));
+                        self.instruct_buf.push(asm!("mov rbx, {size}"));
+                        self.instruct_buf.push(asm!("cqo"));
+                        self.instruct_buf.push(asm!("idiv rbx"));
+                        self.instruct_buf.push(asm!("push rax"));
+                        return Ok(());
                     }
+                    _ => {}
                 }
-                self.instruct_buf.push(asm!("push rcx"));
-            }
-            Expr::Binary(b) => {
-                self.compile_expr(b.left.as_ref());
-                self.compile_expr(b.right.as_ref());
-                self.instruct_buf.push(asm!("pop rbx"));
-                self.instruct_buf.push(asm!("pop rax"));
                 match b.op {
                     Op::Plus => {
                         self.instruct_buf.push(asm!("add rax, rbx"));
+                        self.compile_overflow_check(&expr.v_type, &expr.span, "add");
+                        self.compile_truncate(&expr.v_type);
                         self.instruct_buf.push(asm!("push rax"));
                     }
                     Op::Sub => {
                         self.instruct_buf.push(asm!("sub rax, rbx"));
+                        self.compile_overflow_check(&expr.v_type, &expr.span, "subtract");
+                        self.compile_truncate(&expr.v_type);
                         self.instruct_buf.push(asm!("push rax"));
                     }
                     Op::Multi => {
-                        self.instruct_buf.push(asm!("imul rax, rbx"));
+                        self.compile_mul(&expr.v_type);
+                        self.compile_overflow_check(&expr.v_type, &expr.span, "multiply");
+                        self.compile_truncate(&expr.v_type);
                         self.instruct_buf.push(asm!("push rax"));
                     }
                     Op::Devide => {
-                        self.instruct_buf.push(asm!("cqo"));
-                        self.instruct_buf.push(asm!("idiv rbx"));
+                        self.compile_divisor_check(&expr.v_type, &expr.span, &b.op);
+                        self.compile_div(is_signed(expr));
                         self.instruct_buf.push(asm!("push rax"));
                     }
                     Op::Mod => {
-                        self.instruct_buf.push(asm!("cqo"));
-                        self.instruct_buf.push(asm!("idiv rbx"));
+                        self.compile_divisor_check(&expr.v_type, &expr.span, &b.op);
+                        self.compile_div(is_signed(expr));
                         self.instruct_buf.push(asm!("push rdx"));
                     }
                     Op::Or => {
@@ -656,14 +1960,20 @@ impl Compiler {
                         self.instruct_buf.push(asm!("and rax, rbx"));
                         self.instruct_buf.push(asm!("push rax"));
                     }
+                    Op::Xor => {
+                        self.instruct_buf.push(asm!("xor rax, rbx"));
+                        self.instruct_buf.push(asm!("push rax"));
+                    }
                     Op::Lsh => {
                         self.instruct_buf.push(asm!("mov rcx, rbx"));
                         self.instruct_buf.push(asm!("sal rax, cl"));
+                        self.compile_truncate(&expr.v_type);
                         self.instruct_buf.push(asm!("push rax"));
                     }
                     Op::Rsh => {
                         self.instruct_buf.push(asm!("mov rcx, rbx"));
-                        self.instruct_buf.push(asm!("sar rax, cl"));
+                        let shift = if is_signed(expr) { "sar" } else { "shr" };
+                        self.instruct_buf.push(asm!("{shift} rax, cl"));
                         self.instruct_buf.push(asm!("push rax"));
                     }
                     Op::Not => {
@@ -671,25 +1981,49 @@ impl Compiler {
                     }
                 }
             }
-            Expr::String(str) => {
-                let id = self.data_buf.len();
-                let data_array = Self::asmfy_string(str);
-                self.data_buf.push(asm!("data{id} db {}", data_array));
-                self.data_buf.push(asm!("len{id} equ $ - data{id}"));
+            ExprKind::ArrayLiteral(_) => {
+                return Err(Diagnostic::error(
+                    "Array literals can only initialize array variables",
+                )
+                .with_span(expr.span.clone()));
+            }
+            ExprKind::String(str) => {
+                let id = self.string_data(str);
                 self.instruct_buf.push(asm!("push data{id}"));
                 self.instruct_buf.push(asm!("push len{id}"));
-                // data6524 db "<str>"
-                // len6524     data6524
-                // push len6524jkjk
-                // push data6524
-                // self.instruct_buf.push(asm!("push 13"));
-            }
-            Expr::Unary(u) => {
-                self.compile_unary(u);
+            }
+            ExprKind::Slice(sl) => {
+                self.compile_slice(sl, &expr.span)?;
+            }
+            ExprKind::Len(e) => match &e.v_type {
+                // The length of an array is known at compile time
+                Some(VariableType::Array(_, len)) => {
+                    self.instruct_buf.push(asm!("push {len}"));
+                }
+                _ => {
+                    self.compile_expr(e)?;
+                    self.instruct_buf.push(asm!("pop rax"));
+                    self.instruct_buf.push(asm!("pop rbx"));
+                    self.instruct_buf.push(asm!("push rax"));
+                }
+            },
+            ExprKind::Unary(u) => {
+                self.compile_unary(u)?;
                 self.instruct_buf.push(asm!("pop rax"));
                 match u.op {
+                    // Negating a float flips its sign bit
+                    Op::Sub if expr.v_type == Some(VariableType::F32) => {
+                        self.instruct_buf.push(asm!("btc rax, 31"));
+                        self.instruct_buf.push(asm!("push rax"));
+                    }
+                    Op::Sub if expr.v_type == Some(VariableType::F64) => {
+                        self.instruct_buf.push(asm!("btc rax, 63"));
+                        self.instruct_buf.push(asm!("push rax"));
+                    }
                     Op::Sub => {
                         self.instruct_buf.push(asm!("neg rax"));
+                        self.compile_overflow_check(&expr.v_type, &expr.span, "negate");
+                        self.compile_truncate(&expr.v_type);
                         self.instruct_buf.push(asm!("push rax"));
                     }
                     Op::Plus => {
@@ -697,6 +2031,7 @@ impl Compiler {
                     }
                     Op::Not => {
                         self.instruct_buf.push(asm!("not rax"));
+                        self.compile_truncate(&expr.v_type);
                         self.instruct_buf.push(asm!("push rax"));
                     }
                     _ => {
@@ -704,81 +2039,312 @@ impl Compiler {
                     }
                 }
             }
-            Expr::FunctionCall(fc) => {
-                self.compile_function_call(fc);
+            ExprKind::FunctionCall(fc) => {
+                self.compile_function_call(fc, &expr.span)?;
             }
-            Expr::Ptr(e) => {
-                self.compile_ptr(e);
+            ExprKind::Cast(c) => {
+                self.compile_cast(c)?;
             }
-            Expr::ArrayIndex(ai) => {
-                let v_map = self.find_variable(ai.ident.clone()).unwrap_or_else(|| {
-                    eprintln!(
-                        "Error: Trying to access an Undifined variable ({})",
-                        ai.ident
-                    );
-                    exit(1);
-                });
-                self.compile_expr(&ai.indexer);
-                self.instruct_buf.push(asm!("pop rbx"));
-                // TODO: Add Item size to v_map
-                let mem_acss = format!(
-                    "{} [rbp-{}+rbx*{}]",
-                    mem_word(v_map.item_size),
-                    v_map.offset + v_map.size,
-                    v_map.item_size
-                );
-                let reg = rbs("a", v_map.item_size);
-                self.instruct_buf.push(asm!("mov {reg},{mem_acss}"));
-                self.instruct_buf.push(asm!("push {reg}"));
+            ExprKind::SizeOf(s) => {
+                // Evaluated at compile time, the expression is not run
+                let size = match s {
+                    SizeOf::Type(t) => self.type_size(t),
+                    SizeOf::Expr(e) => match &e.kind {
+                        ExprKind::String(str) => str.len(),
+                        _ => self.value_size(e),
+                    },
+                };
+                self.instruct_buf.push(asm!("push {size}"));
+            }
+            ExprKind::Ptr(e) => {
+                self.compile_addr(e)?;
+            }
+            ExprKind::Deref(_) | ExprKind::ArrayIndex(_) | ExprKind::Field(_) => {
+                self.compile_addr(expr)?;
+                self.compile_load(expr);
             }
         }
+        Ok(())
     }
 
-    fn compile_unary(&mut self, unary: &UnaryExpr) {
-        self.compile_expr(&unary.right);
+    /// Exits the program with the message and the location of the code
+    fn compile_panic(&mut self, span: &Span, message: &str) {
+        let id = self.string_data(&format!("{}:{}: {message}\n", span.file_path, span.line));
+        self.instruct_buf.push(asm!("mov rsi, data{id}"));
+        self.instruct_buf.push(asm!("mov rdx, len{id}"));
+        self.instruct_buf.push(asm!("jmp __nmt_panic"));
     }
 
-    fn compile_ptr(&mut self, expr: &Expr) {
-        match expr {
-            Expr::Variable(v) => {
-                let v_map = self.get_vriable_map(v);
-                self.instruct_buf.push(asm!("mov rax, rbp"));
-                self.instruct_buf
-                    .push(asm!("sub rax, {}", v_map.offset + v_map.size));
+    /// Writes the location and a message of type str to stderr and exits with 1
+    /// String literals are stored with the location as a single message
+    fn compile_message_panic(&mut self, message: &Expr, span: &Span) -> Result<(), Diagnostic> {
+        if let ExprKind::String(str) = &message.kind {
+            self.compile_panic(span, str);
+            return Ok(());
+        }
+        self.compile_expr(message)?;
+        let id = self.string_data(&format!("{}:{}: ", span.file_path, span.line));
+        self.instruct_buf.push(asm!("mov rax, 1"));
+        self.instruct_buf.push(asm!("mov rdi, 2"));
+        self.instruct_buf.push(asm!("mov rsi, data{id}"));
+        self.instruct_buf.push(asm!("mov rdx, len{id}"));
+        self.instruct_buf.push(asm!("syscall"));
+        self.instruct_buf.push(asm!("mov rax, 1"));
+        self.instruct_buf.push(asm!("mov rdi, 2"));
+        self.instruct_buf.push(asm!("pop rdx"));
+        self.instruct_buf.push(asm!("pop rsi"));
+        self.instruct_buf.push(asm!("syscall"));
+        let id = self.string_data("\n");
+        self.instruct_buf.push(asm!("mov rsi, data{id}"));
+        self.instruct_buf.push(asm!("mov rdx, len{id}"));
+        self.instruct_buf.push(asm!("jmp __nmt_panic"));
+        Ok(())
+    }
+
+    /// Adds the string to the data section and returns its id
+    /// data{id} is the address of the string and len{id} its length
+    fn string_data(&mut self, str: &str) -> usize {
+        let id = self.data_buf.len();
+        let data_array = Self::asmfy_string(str);
+        self.data_buf.push(asm!("data{id} db {}", data_array));
+        self.data_buf.push(asm!("len{id} equ $ - data{id}"));
+        id
+    }
+
+    /// Pushes the view of the items from start up to end
+    /// A missing start is 0 and a missing end is the length of the base
+    fn compile_slice(&mut self, sl: &Slice, span: &Span) -> Result<(), Diagnostic> {
+        let item_size = match &sl.base.v_type {
+            Some(
+                VariableType::Array(t, _) | VariableType::Pointer(Some(t)) | VariableType::Slice(t),
+            ) => self.type_size(t),
+            _ => 1,
+        };
+        match &sl.base.v_type {
+            Some(VariableType::Array(_, len)) => {
+                self.compile_addr(&sl.base)?;
+                self.instruct_buf.push(asm!("push {len}"));
+            }
+            Some(VariableType::Pointer(_)) => {
+                // The end of a pointer slice is checked to be given
+                // and the length of the pointee is unknown
+                self.compile_expr(&sl.base)?;
+                self.instruct_buf.push(asm!("push -1"));
+            }
+            _ => self.compile_expr(&sl.base)?,
+        }
+        match &sl.start {
+            Some(start) => self.compile_expr(start)?,
+            None => self.instruct_buf.push(asm!("push 0")),
+        }
+        match &sl.end {
+            Some(end) => self.compile_expr(end)?,
+            None => self.instruct_buf.push(asm!("push qword [rsp+8]")),
+        }
+        self.instruct_buf.push(asm!("pop rcx"));
+        self.instruct_buf.push(asm!("pop rbx"));
+        self.instruct_buf.push(asm!("pop rdx"));
+        self.instruct_buf.push(asm!("pop rax"));
+        if self.options.bounds_checks {
+            // start <= end <= len
+            let tag = self.instruct_buf.len();
+            self.instruct_buf.push(asm!("cmp rcx, rdx"));
+            self.instruct_buf.push(asm!("ja .L{tag}_outofbounds"));
+            self.instruct_buf.push(asm!("cmp rbx, rcx"));
+            self.instruct_buf.push(asm!("jbe .L{tag}_inbounds"));
+            self.instruct_buf.push(asm!(".L{tag}_outofbounds:"));
+            self.compile_panic(span, "slice out of bounds");
+            self.instruct_buf.push(asm!(".L{tag}_inbounds:"));
+        }
+        self.instruct_buf.push(asm!("sub rcx, rbx"));
+        if item_size != 1 {
+            self.instruct_buf.push(asm!("imul rbx, {item_size}"));
+        }
+        self.instruct_buf.push(asm!("add rax, rbx"));
+        self.instruct_buf.push(asm!("push rax"));
+        self.instruct_buf.push(asm!("push rcx"));
+        Ok(())
+    }
+
+    /// Both sides are converted to the float type of the result
+    fn compile_float_binary(&mut self, b: &BinaryExpr, t: &VariableType) -> Result<(), Diagnostic> {
+        self.compile_expr(b.left.as_ref())?;
+        self.compile_convert(&b.left.v_type, t);
+        self.compile_expr(b.right.as_ref())?;
+        self.compile_convert(&b.right.v_type, t);
+        self.instruct_buf.push(asm!("pop rbx"));
+        self.instruct_buf.push(asm!("pop rax"));
+        self.instruct_buf.push(to_xmm("xmm0", "a", t));
+        self.instruct_buf.push(to_xmm("xmm1", "b", t));
+        let instr = match b.op {
+            Op::Plus => "add",
+            Op::Sub => "sub",
+            Op::Multi => "mul",
+            Op::Devide => "div",
+            _ => unreachable!("Checked by the checker"),
+        };
+        self.instruct_buf
+            .push(asm!("{instr}{} xmm0, xmm1", float_suffix(t)));
+        self.instruct_buf.push(from_xmm("a", "xmm0", t));
+        self.instruct_buf.push(asm!("push rax"));
+        Ok(())
+    }
+
+    /// Integers are truncated to the target and extended by its signedness
+    /// Casting to bool gives 1 for any value other than zero
+    fn compile_cast(&mut self, c: &Cast) -> Result<(), Diagnostic> {
+        self.compile_expr(&c.expr)?;
+        self.compile_convert(&c.expr.v_type, &c.target);
+        if c.target == VariableType::Bool {
+            self.instruct_buf.push(asm!("pop rax"));
+            self.instruct_buf.push(asm!("test rax, rax"));
+            self.instruct_buf.push(asm!("setne al"));
+            self.instruct_buf.push(asm!("movzx rax, al"));
+            self.instruct_buf.push(asm!("push rax"));
+        }
+        Ok(())
+    }
+
+    /// Converts the value on top of the stack between integer and float types
+    /// Integers are truncated to a smaller type or a type of another signedness
+    fn compile_convert(&mut self, from: &Option<VariableType>, to: &VariableType) {
+        let Some(from) = from else {
+            return;
+        };
+        if *from == VariableType::String && *to != VariableType::String {
+            // only the address of the string is kept
+            self.instruct_buf.push(asm!("pop rax"));
+            return;
+        }
+        if from == to {
+            return;
+        }
+        if !from.is_float() && !to.is_float() {
+            let (Some(from_size), Some(size @ (1 | 2 | 4))) = (from.int_size(), to.int_size())
+            else {
+                return;
+            };
+            // Widening keeps the value unless a negative value becomes unsigned
+            let keeps_value = (from_size < size && (to.is_signed() || !from.is_signed()))
+                || (from_size == size && from.is_signed() == to.is_signed());
+            if *to != VariableType::Bool && !keeps_value {
+                self.instruct_buf.push(asm!("pop rax"));
+                self.instruct_buf.push(extend_rax(size, to.is_signed()));
                 self.instruct_buf.push(asm!("push rax"));
             }
-            _ => {
-                todo!("Impl Pointers");
+            return;
+        }
+        self.instruct_buf.push(asm!("pop rax"));
+        match (from, to) {
+            (VariableType::F32, VariableType::F64) => {
+                self.instruct_buf.push(to_xmm("xmm0", "a", from));
+                self.instruct_buf.push(asm!("cvtss2sd xmm0, xmm0"));
+            }
+            (VariableType::F64, VariableType::F32) => {
+                self.instruct_buf.push(to_xmm("xmm0", "a", from));
+                self.instruct_buf.push(asm!("cvtsd2ss xmm0, xmm0"));
+            }
+            (_, to) if to.is_float() => {
+                self.instruct_buf
+                    .push(asm!("cvtsi2{} xmm0, rax", float_suffix(to)));
+            }
+            (from, _) => {
+                // Floats are truncated towards zero
+                self.instruct_buf.push(to_xmm("xmm0", "a", from));
+                self.instruct_buf
+                    .push(asm!("cvtt{}2si rax, xmm0", float_suffix(from)));
+                self.instruct_buf.push(asm!("push rax"));
+                self.compile_convert(&Some(VariableType::Int), to);
+                return;
             }
         }
+        self.instruct_buf.push(from_xmm("a", "xmm0", to));
+        self.instruct_buf.push(asm!("push rax"));
     }
 
-    fn compile_function_call(&mut self, fc: &FunctionCall) {
-        for (index, arg) in fc.args.iter().enumerate() {
-            self.compile_expr(arg);
-            match arg {
-                Expr::String(_) => {
-                    self.instruct_buf.push(asm!("pop rax"));
+    fn compile_unary(&mut self, unary: &UnaryExpr) -> Result<(), Diagnostic> {
+        self.compile_expr(&unary.right)
+    }
+
+    fn compile_function_call(&mut self, fc: &FunctionCall, span: &Span) -> Result<(), Diagnostic> {
+        // TODO: Setup a unresolved function table
+        let Some(fun) = self.functions_map.get(&fc.ident).cloned() else {
+            return Err(Diagnostic::error(format!(
+                "Function {} is not avaliable in this scope.",
+                &fc.ident
+            ))
+            .with_span(span.clone())
+            .with_note("Make sure you are calling the correct function"));
+        };
+        // Every argument is evaluated before any register is set
+        // so nested calls can not overwrite them
+        for (arg, param) in fc.args.iter().zip(fun.args.iter()) {
+            self.compile_expr(arg)?;
+            self.compile_convert(&arg.v_type, &param.typedef);
+        }
+        let locations = arg_locations(&fun.args);
+        // Number of evaluated slots, strings take two
+        let args_count = locations.len();
+        // System V: arguments on the stack are pushed in reverse order
+        // and rsp has to be aligned to 16 bytes at the call
+        let stack_count = locations
+            .iter()
+            .filter(|l| matches!(l, ArgLocation::Stack(_)))
+            .count();
+        let padding = if stack_count.is_multiple_of(2) { 8 } else { 0 };
+        self.instruct_buf.push(asm!("mov rbx, rsp"));
+        self.instruct_buf.push(asm!("and rsp, -16"));
+        self.instruct_buf.push(asm!("push rbx"));
+        if padding != 0 {
+            self.instruct_buf.push(asm!("sub rsp, {padding}"));
+        }
+        for (index, location) in locations.iter().enumerate().rev() {
+            if let ArgLocation::Stack(_) = location {
+                let offset = 8 * (args_count - 1 - index);
+                self.instruct_buf.push(asm!("push qword [rbx+{offset}]"));
+            }
+        }
+        let mut float_count = 0;
+        for (index, location) in locations.iter().enumerate() {
+            let offset = 8 * (args_count - 1 - index);
+            match location {
+                ArgLocation::Register(reg) if reg.starts_with("xmm") => {
                     self.instruct_buf
-                        .push(asm!("pop {}", function_args_register(index, 8)));
+                        .push(asm!("movq {reg}, qword [rbx+{offset}]"));
+                    float_count += 1;
                 }
-                _ => {
-                    self.instruct_buf
-                        .push(asm!("pop {}", function_args_register(index, 8)));
+                ArgLocation::Register(reg) => {
+                    self.instruct_buf.push(asm!("mov {reg}, [rbx+{offset}]"));
                 }
+                ArgLocation::Stack(_) => {}
             }
         }
-        // TODO: Setup a unresolved function table
-        let fun = self.functions_map.get(&fc.ident).unwrap_or_else(|| {
-            eprintln!("Error: Function {} is not avaliable in this scope.",&fc.ident);
-            eprintln!("Make sure you are calling the correct function");
-            exit(-1);
-        });
-        self.instruct_buf.push(asm!("mov rax, 0"));
+        // al holds the number of vector registers used
+        self.instruct_buf.push(asm!("mov rax, {float_count}"));
         self.instruct_buf.push(asm!("call {}", fc.ident));
-        if fun.ret_type.is_some() {
-            self.instruct_buf.push(asm!("push rax"));
+        self.instruct_buf
+            .push(asm!("add rsp, {}", 8 * stack_count + padding));
+        // restore rsp and drop the evaluated arguments
+        self.instruct_buf.push(asm!("pop rsp"));
+        if args_count != 0 {
+            self.instruct_buf.push(asm!("add rsp, {}", 8 * args_count));
+        }
+        match &fun.ret_type {
+            Some(t) if t.is_float() => {
+                self.instruct_buf.push(from_xmm("a", "xmm0", t));
+                self.instruct_buf.push(asm!("push rax"));
+            }
+            // System V returns the two halves of a string or a slice in rax and rdx
+            Some(t) if t.is_view() => {
+                self.instruct_buf.push(asm!("push rax"));
+                self.instruct_buf.push(asm!("push rdx"));
+            }
+            Some(_) => {
+                self.instruct_buf.push(asm!("push rax"));
+            }
+            None => {}
         }
+        Ok(())
     }
 
     fn asmfy_string(str: &str) -> String {
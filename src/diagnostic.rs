@@ -0,0 +1,195 @@
+/*  Copywrite Under MIT License by mahan farzaneh
+ *
+ *  Span: Location of a piece of source code
+ *  Diagnostic: Error or warning reported by lexer, parser and compiler
+ *
+ * */
+use core::fmt::Display;
+use std::error::Error;
+use std::fs;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Span {
+    pub file_path: String,
+    pub line: usize,
+    pub col: usize,
+    pub end_line: usize,
+    pub end_col: usize,
+}
+
+impl Span {
+    /// Returns a span covering the whole file
+    /// Used for errors that are not related to a piece of code
+    pub fn file(file_path: impl ToString) -> Self {
+        Self {
+            file_path: file_path.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.file_path)
+        } else {
+            write!(f, "{}:{}:{}", self.file_path, self.line, self.col)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub span: Option<Span>,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    /// Returns an error diagnostic without location
+    pub fn error(message: impl ToString) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.to_string(),
+            span: None,
+            notes: Vec::new(),
+        }
+    }
+
+    /// Returns a warning diagnostic without location
+    pub fn warning(message: impl ToString) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.to_string(),
+            span: None,
+            notes: Vec::new(),
+        }
+    }
+
+    /// Attaches the primary location of the diagnostic
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    /// Adds an extra line of information printed after the source
+    pub fn with_note(mut self, note: impl ToString) -> Self {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    /// Formats the diagnostic reading the source line from disk
+    pub fn render(&self) -> String {
+        let source = match &self.span {
+            Some(span) => fs::read_to_string(&span.file_path).unwrap_or_default(),
+            None => String::new(),
+        };
+        self.render_with_source(&source)
+    }
+
+    /// Formats the diagnostic with the offending source line and
+    /// a caret underline under the primary span
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Source code of the file the span is pointing to
+    pub fn render_with_source(&self, source: &str) -> String {
+        let mut text = format!("{}: {}\n", self.severity, self.message);
+        if let Some(span) = &self.span {
+            text.push_str(&format!("  --> {}\n", span));
+            if let Some(line) = source.lines().nth(span.line.wrapping_sub(1)) {
+                let number = span.line.to_string();
+                let gutter = " ".repeat(number.len());
+                let end_col = if span.end_line == span.line && span.end_col > span.col {
+                    span.end_col
+                } else {
+                    span.col + 1
+                };
+                text.push_str(&format!("{gutter} |\n"));
+                text.push_str(&format!("{number} | {line}\n"));
+                text.push_str(&format!(
+                    "{gutter} | {}{}\n",
+                    " ".repeat(span.col.saturating_sub(1)),
+                    "^".repeat(end_col - span.col)
+                ));
+            }
+        }
+        for note in &self.notes {
+            text.push_str(&format!("  = note: {note}\n"));
+        }
+        text
+    }
+}
+
+/// Prints every diagnostic to stderr followed by a summary of errors
+/// Returns the number of errors
+pub fn report(diagnostics: &[Diagnostic]) -> usize {
+    for diag in diagnostics {
+        eprint!("{}", diag.render());
+    }
+    let errors = diagnostics
+        .iter()
+        .filter(|diag| diag.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        eprintln!("{errors} error(s) found");
+    }
+    errors
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.span {
+            Some(span) => write!(f, "{}: {} at {}", self.severity, self.message, span),
+            None => write!(f, "{}: {}", self.severity, self.message),
+        }
+    }
+}
+
+impl Error for Diagnostic {}
+
+impl From<Diagnostic> for Vec<Diagnostic> {
+    fn from(diag: Diagnostic) -> Self {
+        vec![diag]
+    }
+}
+
+#[cfg(test)]
+mod diagnostic_tests {
+    use super::{Diagnostic, Span};
+
+    #[test]
+    fn render_caret_under_span() {
+        let span = Span {
+            file_path: "./test.nmt".to_string(),
+            line: 2,
+            col: 5,
+            end_line: 2,
+            end_col: 8,
+        };
+        let diag = Diagnostic::error("Unexpected Token")
+            .with_span(span)
+            .with_note("remove it");
+        let text = diag.render_with_source("func main() {\n    var a = ;\n}");
+        assert_eq!(
+            text,
+            "error: Unexpected Token\n  --> ./test.nmt:2:5\n  |\n2 |     var a = ;\n  |     ^^^\n  = note: remove it\n"
+        );
+    }
+}
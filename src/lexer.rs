@@ -4,7 +4,7 @@
  *  Token: Turns Source code into An Iteration of tokens
  *
  * */
-use std::process::exit;
+use crate::diagnostic::{Diagnostic, Span};
 type Loc = (String, usize, usize);
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -12,9 +12,9 @@ pub enum TokenType {
     /// Identifies a variable or functuin e.g: a, main, print
     Identifier,
     /// Numeric value e.g: 12 ,0xf3, 0b110
-    Int(i32),
+    Int(i64),
     /// Floating value e.g: 0.5
-    Float(f32),
+    Float(f64),
     /// Character Literal e.g: 'A', '9', '\n'
     Char(char),
     /// String Literal e.g: "Hello world", "hi\nhello"
@@ -57,6 +57,28 @@ pub enum TokenType {
     Continue,
     /// Keyword print
     Print,
+    /// Keyword put
+    Put,
+    /// Keyword panic
+    Panic,
+    /// Keyword assert
+    Assert,
+    /// Keyword struct
+    Struct,
+    /// Keyword enum
+    Enum,
+    /// Keyword match
+    Match,
+    /// Keyword for
+    For,
+    /// Keyword in
+    In,
+    /// Keyword not (logical not)
+    LogicalNot,
+    /// Keyword as
+    As,
+    /// Keyword sizeof
+    SizeOf,
     /// Keyword true
     True,
     /// Keyword false
@@ -95,6 +117,12 @@ pub enum TokenType {
     And,
     /// "|" logical or
     Or,
+    /// "^" Binary Xor Operation
+    Xor,
+    /// "&&" Short-circuit logical and
+    DoubleAnd,
+    /// "||" Short-circuit logical or
+    DoubleOr,
     /// "#" NOT DEFINED YET
     Log,
     /// "?" Question Mark
@@ -123,6 +151,12 @@ pub enum TokenType {
     CCurly,
     /// "." Refrence
     Dot,
+    /// ".." Exclusive range
+    DoubleDot,
+    /// "..=" Inclusive range
+    DoubleDotEq,
+    /// "=>" Match arm
+    FatArrow,
     /// END OF FILE
     Eof,
     /// START OF FILE
@@ -144,6 +178,7 @@ impl TokenType {
 pub struct Token {
     pub file_path: String,
     pub col: usize,
+    pub end_col: usize,
     pub line: usize,
     pub literal: String,
     pub t_type: TokenType,
@@ -156,7 +191,7 @@ impl Token {
     ///  
     /// * `t_type` - TokenType extracted by lexer
     /// * `literal` - The String Literal related to the token
-    /// * `loc` - The location of the token
+    /// * `loc` - The location of the first character of the token
     ///
     /// # Examples
     ///
@@ -168,6 +203,7 @@ impl Token {
     /// ```
     pub fn new(t_type: TokenType, literal: String, loc: Loc) -> Self {
         Self {
+            end_col: loc.2 + literal.chars().count(),
             literal,
             t_type,
             file_path: loc.0,
@@ -176,6 +212,17 @@ impl Token {
         }
     }
 
+    /// Returns the location of the token in source code
+    pub fn span(&self) -> Span {
+        Span {
+            file_path: self.file_path.clone(),
+            line: self.line,
+            col: self.col,
+            end_line: self.line,
+            end_col: self.end_col,
+        }
+    }
+
     /// Check if The Type of token is indicating the start
     /// or the end of a file
     pub fn is_empty(&self) -> bool {
@@ -190,6 +237,7 @@ impl Token {
             file_path: String::new(),
             line: 0,
             col: 0,
+            end_col: 0,
         }
     }
 }
@@ -199,6 +247,10 @@ pub struct Lexer {
     pub file_path: String,
     source: Vec<char>,
     pub token: Token,
+    /// Line and end column of the previously consumed token
+    prev_end: (usize, usize),
+    /// Errors recovered from while parsing
+    pub diagnostics: Vec<Diagnostic>,
     cur: usize,
     bol: usize,
     row: usize,
@@ -216,6 +268,8 @@ impl Lexer {
             file_path,
             source: source.chars().collect::<Vec<char>>(),
             token: Token::empty(),
+            prev_end: (0, 0),
+            diagnostics: Vec::new(),
             cur: 0,
             bol: 0,
             row: 0,
@@ -267,45 +321,40 @@ impl Lexer {
         )
     }
 
-    /// Formats the current token location to string
-    pub fn get_loc_string(&self) -> String {
-        let loc: Loc = (
-            self.file_path.clone(),
-            self.row + 1,
-            self.cur - self.bol + 1,
-        );
-        format!("{}:{}:{}", loc.0, loc.1, loc.2)
+    /// Returns an error located at the current cursor of the lexer
+    fn error_here(&self, message: impl ToString) -> Diagnostic {
+        let token = Token::new(TokenType::Sof, String::new(), self.get_loc());
+        Diagnostic::error(message).with_span(token.span())
+    }
+
+    /// Returns an error located at the current token
+    pub fn error(&self, message: impl ToString) -> Diagnostic {
+        Diagnostic::error(message).with_span(self.token.span())
     }
 
     /// Returns type of the current token
-    /// Can exit the program if token is EOF
-    pub fn get_token_type(&self) -> TokenType {
+    /// Returns an Error if token is EOF
+    pub fn get_token_type(&self) -> Result<TokenType, Diagnostic> {
         let tk = self.token.clone();
         if tk.t_type == TokenType::Eof {
-            eprintln!("Expected a Token, found Eof at {}", self.get_loc_string());
-            exit(-1);
+            return Err(self.error("Expected a Token, found Eof"));
         };
-        tk.t_type
+        Ok(tk.t_type)
     }
 
     /// Checks if the current token type matches the giver token type
-    /// Will exit the program if token is not matching
+    /// Returns an Error if token is not matching
     ///
     /// # Arguments
     ///
     /// * `t_type` - TokenType for matching
-    pub fn match_token(&mut self, t_type: TokenType) {
+    pub fn match_token(&mut self, t_type: TokenType) -> Result<(), Diagnostic> {
         let tk = self.token.clone();
         if tk.t_type == t_type {
-            self.next_token();
+            self.next_token()?;
+            Ok(())
         } else {
-            eprintln!(
-                "Expected {:?}, found {:?} at {}",
-                t_type,
-                tk.t_type,
-                self.get_loc_string()
-            );
-            exit(-1);
+            Err(self.error(format!("Expected {:?}, found {:?}", t_type, tk.t_type)))
         }
     }
 
@@ -315,14 +364,28 @@ impl Lexer {
     }
 
     /// Scans the next token and sets the current token to the new token
-    pub fn next_token(&mut self) -> Token {
-        let token = self._next_token();
+    pub fn next_token(&mut self) -> Result<Token, Diagnostic> {
+        let token = self._next_token()?;
+        self.prev_end = (self.token.line, self.token.end_col);
         self.token = token.clone();
-        token
+        Ok(token)
+    }
+
+    /// Returns a span from the start of a node to the end of the last consumed token
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - Span of the first token of the node
+    pub fn span_from(&self, start: &Span) -> Span {
+        Span {
+            end_line: self.prev_end.0,
+            end_col: self.prev_end.1,
+            ..start.clone()
+        }
     }
 
     /// Scans the next token
-    fn _next_token(&mut self) -> Token {
+    fn _next_token(&mut self) -> Result<Token, Diagnostic> {
         self.trim_left();
         while !self.is_empty() {
             if self.source[self.cur] == '~' {
@@ -332,8 +395,9 @@ impl Lexer {
                 break;
             }
         }
+        let loc = self.get_loc();
         if self.is_empty() {
-            return Token::empty();
+            return Ok(Token::new(TokenType::Eof, String::new(), loc));
         }
         let first = self.source[self.cur];
 
@@ -346,21 +410,25 @@ impl Lexer {
             }
             let literal = String::from_iter(self.source[index..self.cur].to_vec());
             match Self::is_keyword(&literal) {
-                Some(keyword_token) => return Token::new(keyword_token, literal, self.get_loc()),
-                None => return Token::new(TokenType::Identifier, literal, self.get_loc()),
+                Some(keyword_token) => return Ok(Token::new(keyword_token, literal, loc)),
+                None => return Ok(Token::new(TokenType::Identifier, literal, loc)),
             }
         }
         if first.is_ascii_digit() {
             let index = self.cur;
             self.drop();
+            // A dot followed by another dot is a range e.g: 1..5
             while !self.is_empty()
-                && (self.source[self.cur].is_ascii_alphanumeric() || self.source[self.cur] == '.')
+                && (self.source[self.cur].is_ascii_alphanumeric()
+                    || (self.source[self.cur] == '.'
+                        && self.source.get(self.cur + 1) != Some(&'.')))
             {
                 self.drop();
             }
             let literal = String::from_iter(self.source[index..self.cur].to_vec());
-            let ttype_and_val = Self::parse_numeric_literal(&literal);
-            return Token::new(ttype_and_val, literal, self.get_loc());
+            let ttype_and_val = Self::parse_numeric_literal(&literal)
+                .map_err(|msg| Diagnostic::error(msg).with_span(self.span_to_cursor(&loc)))?;
+            return Ok(Token::new(ttype_and_val, literal, loc));
         }
         if first == '\'' {
             return self.tokenize_char_literal();
@@ -377,55 +445,75 @@ impl Lexer {
                 if Self::is_single_char_token(next).is_some() {
                     if let Some(dtt) = Self::is_double_char_token(first, next) {
                         self.drop();
-                        return Token::new(
-                            dtt,
-                            String::from_iter(vec![first, next]),
-                            self.get_loc(),
-                        );
+                        if dtt == TokenType::DoubleDot && self.source.get(self.cur) == Some(&'=') {
+                            self.drop();
+                            return Ok(Token::new(TokenType::DoubleDotEq, "..=".to_string(), loc));
+                        }
+                        return Ok(Token::new(dtt, String::from_iter(vec![first, next]), loc));
                     }
                 }
             }
-            return Token::new(tt, first.to_string(), self.get_loc());
+            return Ok(Token::new(tt, first.to_string(), loc));
         }
 
-        eprintln!("Unexpected Character at {}", self.get_loc_string());
-        exit(1);
+        let err = self.error_here(format!("Unexpected Character ({first})"));
+        self.drop();
+        Err(err)
+    }
+
+    /// Returns the span from a location to the current cursor
+    fn span_to_cursor(&self, loc: &Loc) -> Span {
+        Span {
+            file_path: loc.0.clone(),
+            line: loc.1,
+            col: loc.2,
+            end_line: self.row + 1,
+            end_col: self.cur - self.bol + 1,
+        }
     }
 
     /// Tokenses the char literal
     /// ONLY call when current char is (')
-    fn tokenize_char_literal(&mut self) -> Token {
+    fn tokenize_char_literal(&mut self) -> Result<Token, Diagnostic> {
+        let loc = self.get_loc();
         self.drop();
         let literal;
+        if self.is_empty() {
+            return Err(self.error_here("Char literal is not closed properly"));
+        }
         let char = self.source[self.cur];
         if char == '\'' {
-            eprintln!("char literal can not be empty {}", self.get_loc_string());
-            exit(1);
+            return Err(self.error_here("char literal can not be empty"));
         }
         if char == '\\' {
             self.drop();
             if self.is_empty() {
-                eprintln!(
-                    "char literal unfinished escape sequence {}",
-                    self.get_loc_string()
-                );
-                exit(1);
+                return Err(self.error_here("char literal unfinished escape sequence"));
             }
             let escape = self.source[self.cur];
             match escape {
-                'n'  => {literal = '\n';}
-                '\'' => {literal = '\'';}
-                't'  => {literal = '\t';}
-                'r'  => {literal = '\r';}
-                '\\' => {literal = '\\';}
-                '0'  => {literal = '\\';}
+                'n' => {
+                    literal = '\n';
+                }
+                '\'' => {
+                    literal = '\'';
+                }
+                't' => {
+                    literal = '\t';
+                }
+                'r' => {
+                    literal = '\r';
+                }
+                '\\' => {
+                    literal = '\\';
+                }
+                '0' => {
+                    literal = '\\';
+                }
                 _ => {
-                    eprintln!(
-                        "unsupported escape sequence (\\{}) {}",
-                        escape,
-                        self.get_loc_string()
+                    return Err(
+                        self.error_here(format!("unsupported escape sequence (\\{})", escape))
                     );
-                    exit(1);
                 }
             }
             self.drop();
@@ -436,23 +524,21 @@ impl Lexer {
 
         if !self.is_empty() {
             if self.source[self.cur] != '\'' {
-                eprintln!("unsupported char {}", self.get_loc_string());
-                exit(1);
+                return Err(self.error_here("unsupported char"));
             }
             self.drop();
-            Token::new(TokenType::Char(literal), literal.to_string(), self.get_loc())
+            let mut token = Token::new(TokenType::Char(literal), literal.to_string(), loc);
+            token.end_col = self.cur - self.bol + 1;
+            Ok(token)
         } else {
-            eprintln!(
-                "Error: Char literal is not closed properly at {}",
-                self.get_loc_string()
-            );
-            exit(1);
+            Err(self.error_here("Char literal is not closed properly"))
         }
     }
 
     /// Tokenses the string literal
     /// ONLY call when current char is (")
-    fn tokenize_string_literal(&mut self) -> Token {
+    fn tokenize_string_literal(&mut self) -> Result<Token, Diagnostic> {
+        let loc = self.get_loc();
         self.drop();
         let mut literal = String::new();
         while !self.is_empty() {
@@ -461,20 +547,12 @@ impl Lexer {
                 break;
             }
             if char == '\n' {
-                eprintln!(
-                    "string literal not closed before end of line {}",
-                    self.get_loc_string()
-                );
-                exit(1);
+                return Err(self.error_here("string literal not closed before end of line"));
             }
             if char == '\\' {
                 self.drop();
                 if self.is_empty() {
-                    eprintln!(
-                        "string literal unfinished escape sequence {}",
-                        self.get_loc_string()
-                    );
-                    exit(1);
+                    return Err(self.error_here("string literal unfinished escape sequence"));
                 }
 
                 let escape = self.source[self.cur];
@@ -500,12 +578,9 @@ impl Lexer {
                         self.drop();
                     }
                     _ => {
-                        eprintln!(
-                            "unsupported escape sequence (\\{}) {}",
-                            escape,
-                            self.get_loc_string()
+                        return Err(
+                            self.error_here(format!("unsupported escape sequence (\\{})", escape))
                         );
-                        exit(1);
                     }
                 }
             } else {
@@ -515,13 +590,11 @@ impl Lexer {
         }
         if !self.is_empty() {
             self.drop();
-            Token::new(TokenType::String, literal, self.get_loc())
+            let mut token = Token::new(TokenType::String, literal, loc);
+            token.end_col = self.cur - self.bol + 1;
+            Ok(token)
         } else {
-            eprintln!(
-                "Error: String literal is not closed properly at {}",
-                self.get_loc_string()
-            );
-            exit(1);
+            Err(self.error_here("String literal is not closed properly"))
         }
     }
 
@@ -542,6 +615,17 @@ impl Lexer {
             "break" => Some(TokenType::Break),
             "continue" => Some(TokenType::Continue),
             "print" => Some(TokenType::Print),
+            "put" => Some(TokenType::Put),
+            "panic" => Some(TokenType::Panic),
+            "assert" => Some(TokenType::Assert),
+            "struct" => Some(TokenType::Struct),
+            "enum" => Some(TokenType::Enum),
+            "match" => Some(TokenType::Match),
+            "for" => Some(TokenType::For),
+            "in" => Some(TokenType::In),
+            "not" => Some(TokenType::LogicalNot),
+            "as" => Some(TokenType::As),
+            "sizeof" => Some(TokenType::SizeOf),
             "true" => Some(TokenType::True),
             "false" => Some(TokenType::False),
             "include" => Some(TokenType::Include),
@@ -585,6 +669,7 @@ impl Lexer {
             '%' => Some(TokenType::Mod),
             '&' => Some(TokenType::And),
             '|' => Some(TokenType::Or),
+            '^' => Some(TokenType::Xor),
             _ => None,
         }
     }
@@ -613,63 +698,61 @@ impl Lexer {
             "<=" => Some(TokenType::SmallerEq),
             "<<" => Some(TokenType::Lsh),
             ">>" => Some(TokenType::Rsh),
+            ".." => Some(TokenType::DoubleDot),
+            "=>" => Some(TokenType::FatArrow),
+            "&&" => Some(TokenType::DoubleAnd),
+            "||" => Some(TokenType::DoubleOr),
             _ => None,
         }
     }
 
     /// Parse numeric literal to a numeric TokenType
-    /// Can Exit the program if can not parse the lietal
+    /// Returns an Error message if can not parse the lietal
     ///
     /// # Arguments
     ///
     /// * `literal` - token literal that we whant to check
-    fn parse_numeric_literal(literal: &String) -> TokenType {
+    fn parse_numeric_literal(literal: &String) -> Result<TokenType, String> {
         // 0x001 0xff 0b0010
+        // literals up to u64::MAX are kept as the bits of an i64
         let mut lit_chars = literal.chars();
-        if literal.contains('x') {
-            Self::expect_char(&lit_chars.next(), vec!['0']);
-            Self::expect_char(&lit_chars.next(), vec!['x']);
-            let mut value: i32 = 0;
-            for ch in lit_chars {
-                let digit = ch.to_digit(16).unwrap_or_else(|| {
-                    eprintln!("Error: Unknown character in parsing: {}", literal);
-                    exit(-1);
-                });
-                value = (value * 16i32) + digit as i32;
-            }
-            TokenType::Int(value)
-        } else if literal.contains('b') {
-            Self::expect_char(&lit_chars.next(), vec!['0']);
-            Self::expect_char(&lit_chars.next(), vec!['b']);
-            let mut value: i32 = 0;
+        let unknown_char = || format!("Unknown character in parsing: {}", literal);
+        let too_large = || format!("Numeric literal does not fit in 64 bits: {}", literal);
+        if literal.contains('x') || literal.contains('b') {
+            Self::expect_char(&lit_chars.next(), vec!['0'])?;
+            let radix = match Self::expect_char(&lit_chars.next(), vec!['x', 'b'])? {
+                'x' => 16,
+                _ => 2,
+            };
+            let mut value: u64 = 0;
             for ch in lit_chars {
-                let digit = ch.to_digit(2).unwrap_or_else(|| {
-                    eprintln!("Error: Unknown character in parsing: {}", literal);
-                    exit(-1);
-                });
-                value = (value * 2i32) + digit as i32;
+                let digit = ch.to_digit(radix).ok_or_else(unknown_char)?;
+                value = value
+                    .checked_mul(radix as u64)
+                    .and_then(|v| v.checked_add(digit as u64))
+                    .ok_or_else(too_large)?;
             }
-            TokenType::Int(value)
+            Ok(TokenType::Int(value as i64))
         } else if literal.contains('.') {
-            let value: f32 = literal.parse::<f32>().unwrap();
-            TokenType::Float(value)
+            let value: f64 = literal.parse::<f64>().map_err(|_| unknown_char())?;
+            Ok(TokenType::Float(value))
         } else {
-            let value: i32 = literal.parse::<i32>().unwrap();
-            TokenType::Int(value)
+            let value: u64 = literal.parse::<u64>().map_err(|err| match err.kind() {
+                std::num::IntErrorKind::PosOverflow => too_large(),
+                _ => unknown_char(),
+            })?;
+            Ok(TokenType::Int(value as i64))
         }
     }
 
     /// Returns char if exits in a list
-    /// Will Exit the program if no match
-    fn expect_char(copt: &Option<char>, chars: Vec<char>) -> char {
-        let char = copt.unwrap_or_else(|| {
-            eprintln!("Error: Undifined character set for numbers");
-            exit(-1);
-        });
+    /// Returns an Error message if no match
+    fn expect_char(copt: &Option<char>, chars: Vec<char>) -> Result<char, String> {
+        let char = copt.ok_or("Undifined character set for numbers")?;
         if chars.contains(&char) {
-            return char;
+            return Ok(char);
         }
-        char
+        Err(format!("Expected one of {:?} found ({})", chars, char))
     }
 }
 
@@ -682,28 +765,96 @@ mod lexer_tests {
     #[test]
     fn expr_tokens() {
         let mut lexer = Lexer::new(String::new(), "a + (3 * 4) - 2".to_string());
-        assert_eq!(lexer.next_token().t_type, TokenType::Identifier);
-        assert_eq!(lexer.next_token().t_type, TokenType::Plus);
-        assert_eq!(lexer.next_token().t_type, TokenType::OParen);
-        assert_eq!(lexer.next_token().t_type, TokenType::Int(3));
-        assert_eq!(lexer.next_token().t_type, TokenType::Multi);
-        assert_eq!(lexer.next_token().t_type, TokenType::Int(4));
-        assert_eq!(lexer.next_token().t_type, TokenType::CParen);
-        assert_eq!(lexer.next_token().t_type, TokenType::Minus);
-        assert_eq!(lexer.next_token().t_type, TokenType::Int(2));
+        assert_eq!(lexer.next_token().unwrap().t_type, TokenType::Identifier);
+        assert_eq!(lexer.next_token().unwrap().t_type, TokenType::Plus);
+        assert_eq!(lexer.next_token().unwrap().t_type, TokenType::OParen);
+        assert_eq!(lexer.next_token().unwrap().t_type, TokenType::Int(3));
+        assert_eq!(lexer.next_token().unwrap().t_type, TokenType::Multi);
+        assert_eq!(lexer.next_token().unwrap().t_type, TokenType::Int(4));
+        assert_eq!(lexer.next_token().unwrap().t_type, TokenType::CParen);
+        assert_eq!(lexer.next_token().unwrap().t_type, TokenType::Minus);
+        assert_eq!(lexer.next_token().unwrap().t_type, TokenType::Int(2));
     }
 
     #[test]
     fn string_literal() {
         let mut lexer = Lexer::new(String::new(), "\"test\"".to_string());
-        assert_eq!(lexer.tokenize_string_literal().t_type, TokenType::String);
+        assert_eq!(
+            lexer.tokenize_string_literal().unwrap().t_type,
+            TokenType::String
+        );
     }
 
     #[test]
     fn string_literal_escape_seq() {
         let mut lexer = Lexer::new(String::new(), "\"test\\ntest\"".to_string());
-        assert_eq!(lexer.tokenize_string_literal().t_type, TokenType::String);
+        assert_eq!(
+            lexer.tokenize_string_literal().unwrap().t_type,
+            TokenType::String
+        );
         let mut lexer = Lexer::new(String::new(), "\"\\\"test\\\"\"".to_string());
-        assert_eq!(lexer.tokenize_string_literal().t_type, TokenType::String);
+        assert_eq!(
+            lexer.tokenize_string_literal().unwrap().t_type,
+            TokenType::String
+        );
+    }
+
+    #[test]
+    fn range_tokens() {
+        let mut lexer = Lexer::new(String::new(), "1..5 'a'..='z' => 1.5".to_string());
+        assert_eq!(lexer.next_token().unwrap().t_type, TokenType::Int(1));
+        assert_eq!(lexer.next_token().unwrap().t_type, TokenType::DoubleDot);
+        assert_eq!(lexer.next_token().unwrap().t_type, TokenType::Int(5));
+        assert_eq!(lexer.next_token().unwrap().t_type, TokenType::Char('a'));
+        assert_eq!(lexer.next_token().unwrap().t_type, TokenType::DoubleDotEq);
+        assert_eq!(lexer.next_token().unwrap().t_type, TokenType::Char('z'));
+        assert_eq!(lexer.next_token().unwrap().t_type, TokenType::FatArrow);
+        assert_eq!(lexer.next_token().unwrap().t_type, TokenType::Float(1.5));
+    }
+
+    #[test]
+    fn wide_int_literals() {
+        let source = "0xffffffffffffffff 9223372036854775807 0b11 18446744073709551616";
+        let mut lexer = Lexer::new(String::new(), source.to_string());
+        assert_eq!(lexer.next_token().unwrap().t_type, TokenType::Int(-1));
+        assert_eq!(lexer.next_token().unwrap().t_type, TokenType::Int(i64::MAX));
+        assert_eq!(lexer.next_token().unwrap().t_type, TokenType::Int(3));
+        let err = lexer.next_token().unwrap_err();
+        assert_eq!(
+            err.message,
+            "Numeric literal does not fit in 64 bits: 18446744073709551616"
+        );
+    }
+
+    #[test]
+    fn logical_tokens() {
+        let mut lexer = Lexer::new(String::new(), "a && not b || c & d | e".to_string());
+        let expected = [
+            TokenType::Identifier,
+            TokenType::DoubleAnd,
+            TokenType::LogicalNot,
+            TokenType::Identifier,
+            TokenType::DoubleOr,
+            TokenType::Identifier,
+            TokenType::And,
+            TokenType::Identifier,
+            TokenType::Or,
+            TokenType::Identifier,
+        ];
+        for t_type in expected {
+            assert_eq!(lexer.next_token().unwrap().t_type, t_type);
+        }
+    }
+
+    #[test]
+    fn unclosed_string_literal_error() {
+        let mut lexer = Lexer::new("a.nmt".to_string(), "var a = \"test".to_string());
+        for _ in 0..3 {
+            lexer.next_token().unwrap();
+        }
+        let err = lexer.next_token().unwrap_err();
+        assert_eq!(err.message, "String literal is not closed properly");
+        let span = err.span.unwrap();
+        assert_eq!((span.line, span.col), (1, 14));
     }
 }
@@ -1,16 +1,20 @@
 use crate::utils::get_program_name;
 use std::env::args;
 use std::error::Error;
-use std::process::Command;
+use std::process::{exit, Command};
 
 mod asm_generator;
+mod checker;
 mod command_line;
 mod compiler;
+mod diagnostic;
 mod lexer;
 mod parser;
 mod utils;
-use command_line::{help_command, CliArgs};
+use checker::check_file;
+use command_line::{build_options, help_command, CliArgs};
 use compiler::compile_to_asm;
+use diagnostic::report;
 
 // --- Static Compiler Defenition
 pub static VERSION: &str = "v0.0.1-Beta";
@@ -19,10 +23,28 @@ pub static DEBUG: bool = true;
 
 /// Compiles the given file into an executable
 fn compile_command(arg: &mut CliArgs) {
-    compile_to_asm(arg.get());
+    if let Err(diags) = compile_to_asm(arg.get(), build_options(arg)) {
+        report(&diags);
+        exit(1);
+    }
     compile_to_exc(arg.get());
 }
 
+/// Reports every error and warning in the given file without compiling it
+/// Only errors fail the check
+fn check_command(arg: &mut CliArgs) {
+    match check_file(arg.get()) {
+        Ok((_, warnings)) => {
+            report(&warnings);
+            println!("[info] No errors found in {}", arg.get());
+        }
+        Err(diags) => {
+            report(&diags);
+            exit(1);
+        }
+    }
+}
+
 /// Runs External commands for generating the executable
 pub fn compile_to_exc(path: String) {
     let program_name = get_program_name(path);
@@ -76,6 +98,10 @@ fn commands(arg: &mut CliArgs) {
             arg.next();
             compile_command(arg);
         }
+        "check" => {
+            arg.next();
+            check_command(arg);
+        }
         _ => {
             compile_command(arg);
         }
@@ -90,15 +116,20 @@ fn main() -> Result<(), Box<dyn Error>> {
 
 #[cfg(test)]
 mod functional {
-    use crate::{compile_to_exc, compiler::compile_to_asm, utils::get_program_name};
+    use crate::compiler::{compile_to_asm, BuildOptions};
+    use crate::{compile_to_exc, utils::get_program_name};
     use std::{fs::remove_file, process::Command};
 
     fn generate_asm(path: impl ToString) {
-        compile_to_asm(path.to_string());
+        generate_asm_with(path, BuildOptions::debug());
+    }
+
+    fn generate_asm_with(path: impl ToString, options: BuildOptions) {
+        compile_to_asm(path.to_string(), options).unwrap();
         compile_to_exc(path.to_string());
         let program_name = get_program_name(path);
-        remove_file(format!("./build/{}.o", program_name)).unwrap_or_else(|_| ());
-        remove_file(format!("./build/{}.asm", program_name)).unwrap_or_else(|_| ());
+        remove_file(format!("./build/{}.o", program_name)).unwrap_or_default();
+        remove_file(format!("./build/{}.asm", program_name)).unwrap_or_default();
     }
 
     #[test]
@@ -108,12 +139,12 @@ mod functional {
             .output()
             .expect("Error Executing the program!");
         assert!(output.status.success());
-        let expectation = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+        let expectation = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n3\n8\n";
         assert_eq!(
             String::from_utf8(output.stdout).unwrap(),
             expectation.to_string()
         );
-        remove_file("./build/binary_expr").unwrap_or_else(|_| ());
+        remove_file("./build/binary_expr").unwrap_or_default();
     }
 
     #[test]
@@ -128,7 +159,7 @@ mod functional {
             String::from_utf8(output.stdout).unwrap(),
             expectation.to_string()
         );
-        remove_file("./build/compare_expr").unwrap_or_else(|_| ());
+        remove_file("./build/compare_expr").unwrap_or_default();
     }
 
     #[test]
@@ -143,7 +174,7 @@ mod functional {
             String::from_utf8(output.stdout).unwrap(),
             expectation.to_string()
         );
-        remove_file("./build/string_expr").unwrap_or_else(|_| ());
+        remove_file("./build/string_expr").unwrap_or_default();
     }
 
     #[test]
@@ -158,7 +189,7 @@ mod functional {
             String::from_utf8(output.stdout).unwrap(),
             expectation.to_string()
         );
-        remove_file("./build/loops").unwrap_or_else(|_| ());
+        remove_file("./build/loops").unwrap_or_default();
     }
 
     #[test]
@@ -173,7 +204,7 @@ mod functional {
             String::from_utf8(output.stdout).unwrap(),
             expectation.to_string()
         );
-        remove_file("./build/conditions").unwrap_or_else(|_| ());
+        remove_file("./build/conditions").unwrap_or_default();
     }
 
     #[test]
@@ -183,12 +214,12 @@ mod functional {
             .output()
             .expect("Error Executing the program!");
         assert!(output.status.success());
-        let expectation = "1\n2\n";
+        let expectation = "1\n2\n8\n2\n";
         assert_eq!(
             String::from_utf8(output.stdout).unwrap(),
             expectation.to_string()
         );
-        remove_file("./build/functions").unwrap_or_else(|_| ());
+        remove_file("./build/functions").unwrap_or_default();
     }
 
     #[test]
@@ -203,6 +234,367 @@ mod functional {
             String::from_utf8(output.stdout).unwrap(),
             expectation.to_string()
         );
-        remove_file("./build/arrays").unwrap_or_else(|_| ());
+        remove_file("./build/arrays").unwrap_or_default();
+    }
+
+    #[test]
+    fn break_continue_test() {
+        generate_asm("./tests/break_continue.nmt");
+        let output = Command::new("./build/break_continue")
+            .output()
+            .expect("Error Executing the program!");
+        assert!(output.status.success());
+        let expectation = "2\n4\n6\n8\n";
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            expectation.to_string()
+        );
+        remove_file("./build/break_continue").unwrap_or_default();
+    }
+
+    #[test]
+    fn globals_test() {
+        generate_asm("./tests/globals.nmt");
+        let output = Command::new("./build/globals")
+            .output()
+            .expect("Error Executing the program!");
+        assert!(output.status.success());
+        let expectation = "8\n15\n7\n";
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            expectation.to_string()
+        );
+        remove_file("./build/globals").unwrap_or_default();
+    }
+
+    #[test]
+    fn imports_test() {
+        generate_asm("./tests/imports.nmt");
+        let output = Command::new("./build/imports")
+            .output()
+            .expect("Error Executing the program!");
+        assert!(output.status.success());
+        let expectation = "2\n";
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            expectation.to_string()
+        );
+        remove_file("./build/imports").unwrap_or_default();
+    }
+
+    #[test]
+    fn stack_args_test() {
+        generate_asm("./tests/stack_args.nmt");
+        let output = Command::new("./build/stack_args")
+            .output()
+            .expect("Error Executing the program!");
+        assert!(output.status.success());
+        let expectation = "28\n7\n21\n6\n";
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            expectation.to_string()
+        );
+        remove_file("./build/stack_args").unwrap_or_default();
+    }
+
+    #[test]
+    fn early_return_test() {
+        generate_asm("./tests/early_return.nmt");
+        let output = Command::new("./build/early_return")
+            .output()
+            .expect("Error Executing the program!");
+        assert!(output.status.success());
+        let expectation = "3\n0\n0\n1\n7\n2\n";
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            expectation.to_string()
+        );
+        remove_file("./build/early_return").unwrap_or_default();
+    }
+
+    #[test]
+    fn pointers_test() {
+        generate_asm("./tests/pointers.nmt");
+        let output = Command::new("./build/pointers")
+            .output()
+            .expect("Error Executing the program!");
+        assert!(output.status.success());
+        let expectation = "9\n3\n20\n8\n4\n2\n33\nb\nc\n";
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            expectation.to_string()
+        );
+        remove_file("./build/pointers").unwrap_or_default();
+    }
+
+    #[test]
+    fn print_format_test() {
+        generate_asm("./tests/print_format.nmt");
+        let output = Command::new("./build/print_format")
+            .output()
+            .expect("Error Executing the program!");
+        assert!(output.status.success());
+        let expectation = "-2\n0\n18446744073709551615\n0xff\n0b101\n0xffffffffffffffff\na\nB\n97\n1 0x2\n-1234567000\n";
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            expectation.to_string()
+        );
+        remove_file("./build/print_format").unwrap_or_default();
+    }
+
+    #[test]
+    fn structs_test() {
+        generate_asm("./tests/structs.nmt");
+        let output = Command::new("./build/structs")
+            .output()
+            .expect("Error Executing the program!");
+        assert!(output.status.success());
+        let expectation = "12\n6\n-3\nr\n3\n22\n10\n7\n";
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            expectation.to_string()
+        );
+        remove_file("./build/structs").unwrap_or_default();
+    }
+
+    #[test]
+    fn match_stmt_test() {
+        generate_asm("./tests/match.nmt");
+        let output = Command::new("./build/match")
+            .output()
+            .expect("Error Executing the program!");
+        assert!(output.status.success());
+        let expectation = "1\n2\n3\n0\n0\n5\n6\n1\nzaaae.e.e..\n2\n3\n";
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            expectation.to_string()
+        );
+        remove_file("./build/match").unwrap_or_default();
+    }
+
+    #[test]
+    fn for_loops_test() {
+        generate_asm("./tests/for_loops.nmt");
+        let output = Command::new("./build/for_loops")
+            .output()
+            .expect("Error Executing the program!");
+        assert!(output.status.success());
+        let expectation = "012\n14710\n531\n08\nabc\n30\nnmt\n0123\n78\n7\n\
+            3210\n253 254 255 \n67\n-127-128\n321\n3210\n\n";
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            expectation.to_string()
+        );
+        remove_file("./build/for_loops").unwrap_or_default();
+    }
+
+    #[test]
+    fn zero_step_test() {
+        generate_asm_with("./tests/zero_step.nmt", BuildOptions::release());
+        let output = Command::new("./build/zero_step")
+            .output()
+            .expect("Error Executing the program!");
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "");
+        assert_eq!(
+            String::from_utf8(output.stderr).unwrap(),
+            "./tests/zero_step.nmt:4: attempt to step a for loop by zero\n"
+        );
+        remove_file("./build/zero_step").unwrap_or_default();
+    }
+
+    #[test]
+    fn logical_test() {
+        generate_asm("./tests/logical.nmt");
+        let output = Command::new("./build/logical")
+            .output()
+            .expect("Error Executing the program!");
+        assert!(output.status.success());
+        let expectation = "4\n1\n2\n5\n1\n1\n6\n85\n";
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            expectation.to_string()
+        );
+        remove_file("./build/logical").unwrap_or_default();
+    }
+
+    #[test]
+    fn sized_ints_test() {
+        // Wrapping arithmetic is only kept in release builds
+        generate_asm_with("./tests/sized_ints.nmt", BuildOptions::release());
+        let output = Command::new("./build/sized_ints")
+            .output()
+            .expect("Error Executing the program!");
+        assert!(output.status.success());
+        let expectation = "4\n-128\n-128\n-2\n65535\n-3\n4294967295\n2147483647\n18446744073709551615\n9223372036854775807\n15\n1\n9223372036854775807\n70001\n1\n1\n1\n-2\n1\n22\n0\n255\n";
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            expectation.to_string()
+        );
+        remove_file("./build/sized_ints").unwrap_or_default();
+    }
+
+    #[test]
+    fn floats_test() {
+        generate_asm("./tests/floats.nmt");
+        let output = Command::new("./build/floats")
+            .output()
+            .expect("Error Executing the program!");
+        assert!(output.status.success());
+        let expectation = "1.5\n-9.81\n1.5\n2.5\n12.56636\n0.5\n2\n-2.5\n1\n0.05\n3.0\n1.0\n3.5\n\
+            0\n1\n0\n1\nnan\ninf\n-inf\n9.223372e18\n-1.5e20\n";
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            expectation.to_string()
+        );
+        remove_file("./build/floats").unwrap_or_default();
+    }
+
+    #[test]
+    fn casts_test() {
+        generate_asm("./tests/casts.nmt");
+        let output = Command::new("./build/casts")
+            .output()
+            .expect("Error Executing the program!");
+        assert!(output.status.success());
+        let expectation =
+            "44\n255\n-1\n1\n4294967295\n66\nB\n3\n-2\n3.5\n1\n255\n10\n8\n2\n16\n10\n24\n1\n6\n";
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            expectation.to_string()
+        );
+        remove_file("./build/casts").unwrap_or_default();
+    }
+
+    #[test]
+    fn array_literals_test() {
+        generate_asm("./tests/array_literals.nmt");
+        let output = Command::new("./build/array_literals")
+            .output()
+            .expect("Error Executing the program!");
+        assert!(output.status.success());
+        let expectation = "11\n5\n0\ne\n14\n1\n2\n1\n2.0\n-1.25\n297\nh\n10\n0\n6\n";
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            expectation.to_string()
+        );
+        remove_file("./build/array_literals").unwrap_or_default();
+    }
+
+    #[test]
+    fn strings_test() {
+        generate_asm("./tests/strings.nmt");
+        let output = Command::new("./build/strings")
+            .output()
+            .expect("Error Executing the program!");
+        assert!(output.status.success());
+        let expectation = "hello\n5\nnemet\n5\nlanguage\ne\n1\n1\n1\n1\n1\n1\nell\nhello world\n12\nabcd\nwx\n18\nr\n0\nbye\n";
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            expectation.to_string()
+        );
+        remove_file("./build/strings").unwrap_or_default();
+    }
+
+    #[test]
+    fn slices_test() {
+        generate_asm("./tests/slices.nmt");
+        let output = Command::new("./build/slices")
+            .output()
+            .expect("Error Executing the program!");
+        assert!(output.status.success());
+        let expectation = "21\n7\n5\n2\n18\n1400\n40\n5\n20\n12\n5\n11\nabc\n16\n";
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            expectation.to_string()
+        );
+        remove_file("./build/slices").unwrap_or_default();
+    }
+
+    #[test]
+    fn bounds_checks_test() {
+        generate_asm("./tests/bounds.nmt");
+        let output = Command::new("./build/bounds")
+            .output()
+            .expect("Error Executing the program!");
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "3\n2\nbc\n");
+        assert_eq!(
+            String::from_utf8(output.stderr).unwrap(),
+            "./tests/bounds.nmt:14: index out of bounds\n"
+        );
+        // Release builds leave the check out
+        generate_asm_with("./tests/bounds.nmt", BuildOptions::release());
+        let output = Command::new("./build/bounds")
+            .output()
+            .expect("Error Executing the program!");
+        assert!(output.status.success());
+        assert!(String::from_utf8(output.stdout).unwrap().ends_with("99\n"));
+        remove_file("./build/bounds").unwrap_or_default();
+    }
+
+    #[test]
+    fn assert_test() {
+        generate_asm("./tests/assert.nmt");
+        let output = Command::new("./build/assert")
+            .output()
+            .expect("Error Executing the program!");
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "ok\n");
+        assert_eq!(
+            String::from_utf8(output.stderr).unwrap(),
+            "./tests/assert.nmt:4: odd item found\n"
+        );
+        remove_file("./build/assert").unwrap_or_default();
+    }
+
+    #[test]
+    fn exit_status_test() {
+        generate_asm("./tests/exit_status.nmt");
+        let output = Command::new("./build/exit_status")
+            .output()
+            .expect("Error Executing the program!");
+        assert_eq!(output.status.code(), Some(43));
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "7\n");
+        remove_file("./build/exit_status").unwrap_or_default();
+    }
+
+    #[test]
+    fn overflow_checks_test() {
+        generate_asm("./tests/overflow.nmt");
+        let output = Command::new("./build/overflow")
+            .output()
+            .expect("Error Executing the program!");
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            "255\n-4611686018427387904\n9223372036854775806\n"
+        );
+        assert_eq!(
+            String::from_utf8(output.stderr).unwrap(),
+            "./tests/overflow.nmt:10: attempt to add with overflow\n"
+        );
+        // Release builds keep the wrapping arithmetic
+        generate_asm_with("./tests/overflow.nmt", BuildOptions::release());
+        let output = Command::new("./build/overflow")
+            .output()
+            .expect("Error Executing the program!");
+        assert!(output.status.success());
+        assert!(String::from_utf8(output.stdout)
+            .unwrap()
+            .ends_with("-9223372036854775808\n99\n"));
+        remove_file("./build/overflow").unwrap_or_default();
+
+        generate_asm("./tests/divide_by_zero.nmt");
+        let output = Command::new("./build/divide_by_zero")
+            .output()
+            .expect("Error Executing the program!");
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "1\n");
+        assert_eq!(
+            String::from_utf8(output.stderr).unwrap(),
+            "./tests/divide_by_zero.nmt:6: attempt to calculate the remainder with a divisor of zero\n"
+        );
+        remove_file("./build/divide_by_zero").unwrap_or_default();
     }
 }
@@ -0,0 +1,37 @@
+use crate::diagnostic::Span;
+
+use super::expr::Expr;
+
+#[derive(Debug, Clone)]
+pub struct EnumMember {
+    pub ident: String,
+    /// Explicit discriminant e.g: Red = 3
+    pub value: Option<Expr>,
+    pub span: Span,
+}
+
+/// enum Name { Variant, Variant = value, ... }
+#[derive(Debug, Clone)]
+pub struct EnumDef {
+    pub ident: String,
+    pub variants: Vec<EnumMember>,
+    pub span: Span,
+}
+impl EnumDef {
+    /// Returns the discriminant of every variant in order
+    /// Variants without an explicit value follow the previous one
+    pub fn values(&self) -> Vec<i64> {
+        let mut next = 0;
+        let mut values = Vec::new();
+        for variant in &self.variants {
+            let value = variant
+                .value
+                .as_ref()
+                .and_then(|v| v.const_value())
+                .unwrap_or(next);
+            values.push(value);
+            next = value.wrapping_add(1);
+        }
+        values
+    }
+}
@@ -1,27 +1,188 @@
+use crate::diagnostic::Span;
 use crate::lexer::TokenType;
+use crate::parser::stmt::VariableType;
 use core::fmt::Display;
 
 #[derive(Debug, PartialEq, Clone)]
-pub enum Expr {
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+    /// Type of the expression resolved by the checker
+    pub v_type: Option<VariableType>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ExprKind {
     Unary(UnaryExpr),
     Binary(BinaryExpr),
     Compare(CompareExpr),
-    Int(i32),
+    // expr && expr, expr || expr
+    Logical(LogicalExpr),
+    // not expr
+    Not(Box<Expr>),
+    // expr as @type
+    Cast(Cast),
+    // sizeof(@type) sizeof(expr)
+    SizeOf(SizeOf),
+    Int(i64),
+    Float(f64),
     Char(u8),
+    // ptr expr
     Ptr(Box<Expr>),
+    // *expr
+    Deref(Box<Expr>),
     String(String),
+    // [expr, expr, ...]
+    ArrayLiteral(Vec<Expr>),
     Variable(String),
     FunctionCall(FunctionCall),
     ArrayIndex(ArrayIndex),
+    // expr[start..end] where both ends are optional
+    Slice(Slice),
+    // len(expr)
+    Len(Box<Expr>),
+    // expr.field
+    Field(FieldAccess),
+    // Enum::Variant
+    EnumVariant(EnumVariant),
 }
 impl Expr {
-    pub fn is_binary_op(t_token: TokenType) -> bool {
+    pub fn new(kind: ExprKind, span: Span) -> Self {
+        Self {
+            kind,
+            span,
+            v_type: None,
+        }
+    }
+
+    /// Returns true if the expression points to a place in memory
+    pub fn is_lvalue(&self) -> bool {
         matches!(
-            t_token,
-            TokenType::Plus | TokenType::Minus | TokenType::And | TokenType::Or
+            self.kind,
+            ExprKind::Variable(_)
+                | ExprKind::ArrayIndex(_)
+                | ExprKind::Deref(_)
+                | ExprKind::Field(_)
         )
     }
 
+    /// Evaluates the expression at compile time
+    /// Returns None if the value depends on the runtime
+    pub fn const_value(&self) -> Option<i64> {
+        match &self.kind {
+            ExprKind::Int(x) => Some(*x),
+            ExprKind::EnumVariant(ev) => ev.value,
+            ExprKind::Char(c) => Some(*c as i64),
+            ExprKind::Unary(u) => {
+                let right = u.right.const_value()?;
+                match u.op {
+                    Op::Sub => Some(right.wrapping_neg()),
+                    Op::Not => Some(!right),
+                    _ => Some(right),
+                }
+            }
+            ExprKind::Binary(b) => {
+                let left = b.left.const_value()?;
+                let right = b.right.const_value()?;
+                match b.op {
+                    Op::Plus => Some(left.wrapping_add(right)),
+                    Op::Sub => Some(left.wrapping_sub(right)),
+                    Op::Multi => Some(left.wrapping_mul(right)),
+                    Op::Devide => left.checked_div(right),
+                    Op::Mod => left.checked_rem(right),
+                    Op::And => Some(left & right),
+                    Op::Or => Some(left | right),
+                    Op::Xor => Some(left ^ right),
+                    Op::Lsh => Some(left.wrapping_shl(right as u32)),
+                    Op::Rsh => Some(left.wrapping_shr(right as u32)),
+                    Op::Not => None,
+                }
+            }
+            ExprKind::Compare(c) => {
+                let left = c.left.const_value()?;
+                let right = c.right.const_value()?;
+                let res = match c.op {
+                    CompareOp::Eq => left == right,
+                    CompareOp::NotEq => left != right,
+                    CompareOp::Bigger => left > right,
+                    CompareOp::Smaller => left < right,
+                    CompareOp::BiggerEq => left >= right,
+                    CompareOp::SmallerEq => left <= right,
+                };
+                Some(res as i64)
+            }
+            ExprKind::Logical(l) => {
+                let left = l.left.const_value()? != 0;
+                let right = l.right.const_value()? != 0;
+                let res = match l.op {
+                    LogicalOp::And => left && right,
+                    LogicalOp::Or => left || right,
+                };
+                Some(res as i64)
+            }
+            ExprKind::Not(e) => Some((e.const_value()? == 0) as i64),
+            ExprKind::Cast(c) => {
+                let value = c.expr.const_value()?;
+                match (&c.target, c.target.int_size()) {
+                    (VariableType::Bool, _) => Some((value != 0) as i64),
+                    (_, Some(8)) => Some(value),
+                    // Truncated to the target and extended by its signedness
+                    (t, Some(size)) => {
+                        let bits = size as u32 * 8;
+                        let value = value & ((1 << bits) - 1);
+                        match t.is_signed() && value >> (bits - 1) == 1 {
+                            true => Some(value - (1 << bits)),
+                            false => Some(value),
+                        }
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Evaluates a floating point expression at compile time
+    /// Integer constants are converted to floats
+    pub fn const_float(&self) -> Option<f64> {
+        match &self.kind {
+            ExprKind::Float(x) => Some(*x),
+            ExprKind::Unary(u) if u.op == Op::Sub => Some(-u.right.const_float()?),
+            ExprKind::Unary(u) if u.op == Op::Plus => u.right.const_float(),
+            _ => Some(self.const_value()? as f64),
+        }
+    }
+
+    /// Returns true if the expression can initialize data at compile time
+    /// Strings and array literals are stored in place in the data section
+    pub fn is_const(&self) -> bool {
+        match &self.kind {
+            ExprKind::ArrayLiteral(items) => items.iter().all(|item| item.is_const()),
+            ExprKind::String(_) => true,
+            _ => self.const_value().is_some() || self.const_float().is_some(),
+        }
+    }
+
+    /// Binding power of a binary operator, higher binds tighter
+    /// Returns None if the token is not a binary operator
+    pub fn precedence(t_type: TokenType) -> Option<u8> {
+        match t_type {
+            TokenType::Multi | TokenType::Devide | TokenType::Mod => Some(10),
+            TokenType::Plus | TokenType::Minus => Some(9),
+            TokenType::Lsh | TokenType::Rsh => Some(8),
+            TokenType::Bigger | TokenType::Smaller | TokenType::BiggerEq | TokenType::SmallerEq => {
+                Some(7)
+            }
+            TokenType::DoubleEq | TokenType::NotEq => Some(6),
+            TokenType::And => Some(5),
+            TokenType::Xor => Some(4),
+            TokenType::Or => Some(3),
+            TokenType::DoubleAnd => Some(2),
+            TokenType::DoubleOr => Some(1),
+            _ => None,
+        }
+    }
+
     pub fn is_compare_op(t_token: TokenType) -> bool {
         matches!(
             t_token,
@@ -58,6 +219,7 @@ pub enum Op {
     Mod,
     And,
     Or,
+    Xor,
     Lsh,
     Rsh,
 }
@@ -72,6 +234,7 @@ impl Op {
             TokenType::Mod => Self::Mod,
             TokenType::And => Self::And,
             TokenType::Or => Self::Or,
+            TokenType::Xor => Self::Xor,
             TokenType::Lsh => Self::Lsh,
             TokenType::Rsh => Self::Rsh,
             _ => {
@@ -91,6 +254,7 @@ impl Display for Op {
             Op::Mod => write!(f, "%"),
             Op::And => write!(f, "&"),
             Op::Or => write!(f, "|"),
+            Op::Xor => write!(f, "^"),
             Op::Lsh => write!(f, "<<"),
             Op::Rsh => write!(f, ">>"),
         }
@@ -105,10 +269,43 @@ pub struct FunctionCall {
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct ArrayIndex {
-    pub ident: String,
+    pub base: Box<Expr>,
     pub indexer: Box<Expr>,
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct Slice {
+    pub base: Box<Expr>,
+    pub start: Option<Box<Expr>>,
+    pub end: Option<Box<Expr>>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct EnumVariant {
+    pub ident: String,
+    pub variant: String,
+    /// Discriminant of the variant resolved by the checker
+    pub value: Option<i64>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct FieldAccess {
+    pub base: Box<Expr>,
+    pub field: String,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Cast {
+    pub expr: Box<Expr>,
+    pub target: VariableType,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum SizeOf {
+    Type(VariableType),
+    Expr(Box<Expr>),
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct CompareExpr {
     pub left: Box<Expr>,
@@ -116,6 +313,38 @@ pub struct CompareExpr {
     pub right: Box<Expr>,
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct LogicalExpr {
+    pub left: Box<Expr>,
+    pub op: LogicalOp,
+    pub right: Box<Expr>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum LogicalOp {
+    And,
+    Or,
+}
+impl LogicalOp {
+    pub fn from_token_type(t_type: TokenType) -> Self {
+        match t_type {
+            TokenType::DoubleAnd => Self::And,
+            TokenType::DoubleOr => Self::Or,
+            _ => {
+                unreachable!();
+            }
+        }
+    }
+}
+impl Display for LogicalOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogicalOp::And => write!(f, "&&"),
+            LogicalOp::Or => write!(f, "||"),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum CompareOp {
     NotEq,
@@ -139,4 +368,17 @@ impl CompareOp {
             }
         }
     }
+
+    /// The compare that is true when this one is false
+    /// Only holds for ordered values, a compare with NaN is false either way
+    pub fn negated(&self) -> Self {
+        match self {
+            Self::Eq => Self::NotEq,
+            Self::NotEq => Self::Eq,
+            Self::Bigger => Self::SmallerEq,
+            Self::Smaller => Self::BiggerEq,
+            Self::BiggerEq => Self::Smaller,
+            Self::SmallerEq => Self::Bigger,
+        }
+    }
 }
@@ -1,3 +1,4 @@
+use crate::diagnostic::Span;
 use crate::parser::block::Block;
 
 use super::stmt::VariableType;
@@ -6,6 +7,7 @@ use super::stmt::VariableType;
 pub struct FunctionArg {
     pub ident: String,
     pub typedef: VariableType,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
@@ -14,4 +16,5 @@ pub struct Function {
     pub args: Vec<FunctionArg>,
     pub block: Block,
     pub ret_type: Option<VariableType>,
+    pub span: Span,
 }
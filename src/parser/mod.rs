@@ -1,25 +1,40 @@
 pub mod block;
+pub mod enums;
 pub mod expr;
 pub mod function;
 pub mod program;
 pub mod stmt;
+pub mod structs;
 use std::fs;
-use std::process::exit;
 
+use crate::diagnostic::{Diagnostic, Span};
 use crate::lexer::{Lexer, TokenType};
 
 use crate::parser::block::*;
+use crate::parser::enums::*;
 use crate::parser::expr::{
-    ArrayIndex, BinaryExpr, CompareExpr, CompareOp, Expr, FunctionCall, Op, UnaryExpr,
+    ArrayIndex, BinaryExpr, Cast, CompareExpr, CompareOp, EnumVariant, Expr, ExprKind, FieldAccess,
+    FunctionCall, LogicalExpr, LogicalOp, Op, SizeOf, Slice, UnaryExpr,
 };
 use crate::parser::function::*;
 use crate::parser::program::*;
 use crate::parser::stmt::*;
+use crate::parser::structs::*;
 
-pub fn parse_file(path: String) -> ProgramFile {
-    let source = fs::read_to_string(path.clone()).expect("Can not Read the file");
+/// Parses a file reporting every syntax error found in it
+pub fn parse_file(path: String) -> Result<ProgramFile, Vec<Diagnostic>> {
+    let source =
+        fs::read_to_string(path.clone()).map_err(|err| {
+            vec![Diagnostic::error(format!("Can not Read the file: {err}"))
+                .with_span(Span::file(&path))]
+        })?;
     let mut lexer = Lexer::new(path, source);
-    program(&mut lexer)
+    let program = program(&mut lexer);
+    if lexer.diagnostics.is_empty() {
+        Ok(program)
+    } else {
+        Err(lexer.diagnostics)
+    }
 }
 
 // -4 -> 4 neg
@@ -27,183 +42,298 @@ pub fn parse_file(path: String) -> ProgramFile {
 // 4 * 3 + 6 -> 4 3 * 6 +
 // 4 + (3 + 6) -> 3 6 + 4 +
 // -(4 * cos(0) + 2 - 6) -> 4 cos(0) * 2 + 6 - neg
-pub fn expr(lexer: &mut Lexer) -> Expr {
-    let mut term_expr = term(lexer);
+pub fn expr(lexer: &mut Lexer) -> Result<Expr, Diagnostic> {
+    binary_expr(lexer, 1)
+}
+
+// Precedence climbing over the table in Expr::precedence
+// a + b * c == d & e -> ((a + (b * c)) == d) & e
+// operators of the same precedence group to the left
+fn binary_expr(lexer: &mut Lexer, min_prec: u8) -> Result<Expr, Diagnostic> {
+    let mut left = factor(lexer)?;
     loop {
-        let t_type = lexer.get_token_type();
-        if Expr::is_binary_op(t_type) {
-            let op = Op::from_token_type(t_type);
-            lexer.next_token();
-            let right = term(lexer);
-            term_expr = Expr::Binary(BinaryExpr {
-                left: Box::new(term_expr),
-                op,
-                right: Box::new(right),
+        let t_type = lexer.get_token_type()?;
+        // as binds tighter than every binary operator
+        if t_type == TokenType::As {
+            lexer.match_token(TokenType::As)?;
+            let target = type_def(lexer)?;
+            let span = lexer.span_from(&left.span);
+            let kind = ExprKind::Cast(Cast {
+                expr: Box::new(left),
+                target,
             });
-        } else if Expr::is_compare_op(t_type) {
-            let op = CompareOp::from_token_type(lexer.get_token_type());
-            lexer.next_token();
-            let right = term(lexer);
-            term_expr = Expr::Compare(CompareExpr {
-                left: Box::new(term_expr),
-                op,
-                right: Box::new(right),
-            });
-        } else {
-            break;
+            left = Expr::new(kind, span);
+            continue;
         }
+        let prec = match Expr::precedence(t_type) {
+            Some(prec) if prec >= min_prec => prec,
+            _ => break,
+        };
+        lexer.next_token()?;
+        let right = Box::new(binary_expr(lexer, prec + 1)?);
+        let span = lexer.span_from(&left.span);
+        let left_expr = Box::new(left);
+        let kind = match t_type {
+            TokenType::DoubleAnd | TokenType::DoubleOr => ExprKind::Logical(LogicalExpr {
+                left: left_expr,
+                op: LogicalOp::from_token_type(t_type),
+                right,
+            }),
+            t_type if Expr::is_compare_op(t_type) => ExprKind::Compare(CompareExpr {
+                left: left_expr,
+                op: CompareOp::from_token_type(t_type),
+                right,
+            }),
+            t_type => ExprKind::Binary(BinaryExpr {
+                left: left_expr,
+                op: Op::from_token_type(t_type),
+                right,
+            }),
+        };
+        left = Expr::new(kind, span);
     }
-    term_expr
+    Ok(left)
 }
 
-pub fn term(lexer: &mut Lexer) -> Expr {
-    let mut left = factor(lexer);
-    while lexer.get_token_type() == TokenType::Multi
-        || lexer.get_token_type() == TokenType::Devide
-        || lexer.get_token_type() == TokenType::Mod
-        || lexer.get_token_type() == TokenType::Lsh
-        || lexer.get_token_type() == TokenType::Rsh
-    {
-        let op = Op::from_token_type(lexer.get_token_type());
-        lexer.next_token();
-        let right = factor(lexer);
-        left = Expr::Binary(BinaryExpr {
-            left: Box::new(left),
-            op,
-            right: Box::new(right),
-        });
-    }
-    left
-}
-
-pub fn factor(lexer: &mut Lexer) -> Expr {
-    match lexer.get_token_type() {
+pub fn factor(lexer: &mut Lexer) -> Result<Expr, Diagnostic> {
+    let start = lexer.get_token().span();
+    let kind = match lexer.get_token_type()? {
         TokenType::OParen => {
-            lexer.match_token(TokenType::OParen);
-            let value = expr(lexer);
-            lexer.match_token(TokenType::CParen);
-            value
+            lexer.match_token(TokenType::OParen)?;
+            let value = expr(lexer)?;
+            lexer.match_token(TokenType::CParen)?;
+            return postfix(lexer, Expr::new(value.kind, lexer.span_from(&start)));
         }
         TokenType::Plus | TokenType::Minus | TokenType::Not => {
-            let op = Op::from_token_type(lexer.get_token_type());
-            lexer.next_token();
-            let value = factor(lexer);
-            Expr::Unary(UnaryExpr {
+            let op = Op::from_token_type(lexer.get_token_type()?);
+            lexer.next_token()?;
+            let value = factor(lexer)?;
+            ExprKind::Unary(UnaryExpr {
                 op,
                 right: Box::new(value),
             })
         }
+        TokenType::SizeOf => {
+            // sizeof(@type) or sizeof(expr)
+            lexer.match_token(TokenType::SizeOf)?;
+            lexer.match_token(TokenType::OParen)?;
+            let size_of = match lexer.get_token_type()? {
+                TokenType::ATSign => SizeOf::Type(type_def(lexer)?),
+                _ => SizeOf::Expr(Box::new(expr(lexer)?)),
+            };
+            lexer.match_token(TokenType::CParen)?;
+            ExprKind::SizeOf(size_of)
+        }
+        TokenType::LogicalNot => {
+            lexer.match_token(TokenType::LogicalNot)?;
+            let value = factor(lexer)?;
+            ExprKind::Not(Box::new(value))
+        }
         TokenType::String => {
             let str_token = lexer.get_token();
-            lexer.next_token();
-            Expr::String(str_token.literal)
+            lexer.next_token()?;
+            ExprKind::String(str_token.literal)
         }
+        TokenType::OBracket => ExprKind::ArrayLiteral(array_literal(lexer)?),
         TokenType::Ptr => {
-            lexer.match_token(TokenType::Ptr);
-            let value = expr(lexer);
-            Expr::Ptr(Box::new(value))
+            lexer.match_token(TokenType::Ptr)?;
+            let value = factor(lexer)?;
+            ExprKind::Ptr(Box::new(value))
+        }
+        TokenType::Multi => {
+            lexer.match_token(TokenType::Multi)?;
+            let value = factor(lexer)?;
+            ExprKind::Deref(Box::new(value))
         }
         TokenType::True => {
-            lexer.match_token(TokenType::True);
-            Expr::Int(1)
+            lexer.match_token(TokenType::True)?;
+            ExprKind::Int(1)
         }
         TokenType::False => {
-            lexer.match_token(TokenType::False);
-            Expr::Int(0)
+            lexer.match_token(TokenType::False)?;
+            ExprKind::Int(0)
         }
         TokenType::Char(c) => {
-            lexer.next_token();
-            Expr::Char(c as u8)
+            lexer.next_token()?;
+            ExprKind::Char(c as u8)
         }
         TokenType::Int(val) => {
-            lexer.next_token();
-            Expr::Int(val)
+            lexer.next_token()?;
+            ExprKind::Int(val)
+        }
+        TokenType::Float(val) => {
+            lexer.next_token()?;
+            ExprKind::Float(val)
         }
         TokenType::Identifier => {
             let ident_name = lexer.get_token().literal;
-            if lexer.next_token().is_empty() {
-                return Expr::Variable(ident_name);
+            if lexer.next_token()?.is_empty() {
+                return Ok(Expr::new(
+                    ExprKind::Variable(ident_name),
+                    lexer.span_from(&start),
+                ));
             }
-            match lexer.get_token_type() {
+            let kind = match lexer.get_token_type()? {
+                // len is a builtin and not a function
+                TokenType::OParen if ident_name == "len" => {
+                    lexer.match_token(TokenType::OParen)?;
+                    let value = expr(lexer)?;
+                    lexer.match_token(TokenType::CParen)?;
+                    ExprKind::Len(Box::new(value))
+                }
                 TokenType::OParen => {
-                    let args = function_call_args(lexer);
-                    Expr::FunctionCall(FunctionCall {
+                    let args = function_call_args(lexer)?;
+                    ExprKind::FunctionCall(FunctionCall {
                         ident: ident_name,
                         args,
                     })
                 }
-                TokenType::OBracket => {
-                    let indexer = array_indexer(lexer);
-                    Expr::ArrayIndex(ArrayIndex {
+                TokenType::DoubleColon => {
+                    lexer.match_token(TokenType::DoubleColon)?;
+                    let variant = lexer.get_token().literal;
+                    lexer.match_token(TokenType::Identifier)?;
+                    ExprKind::EnumVariant(EnumVariant {
                         ident: ident_name,
-                        indexer: Box::new(indexer),
+                        variant,
+                        value: None,
                     })
                 }
-                _ => Expr::Variable(ident_name),
-            }
+                _ => ExprKind::Variable(ident_name),
+            };
+            return postfix(lexer, Expr::new(kind, lexer.span_from(&start)));
         }
-        _ => {
-            eprintln!(
-                "Unexpected Token ({:?}) while parsing expr at {}",
-                lexer.get_token_type(),
-                lexer.get_loc_string()
-            );
-            exit(-1);
+        t_type => {
+            return Err(lexer.error(format!(
+                "Unexpected Token ({:?}) while parsing expr",
+                t_type
+            )))
         }
+    };
+    Ok(Expr::new(kind, lexer.span_from(&start)))
+}
+
+/// Parses the indexing that comes after a value e.g: a[1][2]
+pub fn postfix(lexer: &mut Lexer, mut value: Expr) -> Result<Expr, Diagnostic> {
+    loop {
+        let kind = match lexer.get_token().t_type {
+            TokenType::OBracket => array_indexer(lexer, value.clone())?,
+            TokenType::Dot => {
+                lexer.match_token(TokenType::Dot)?;
+                let field = lexer.get_token().literal;
+                lexer.match_token(TokenType::Identifier)?;
+                ExprKind::Field(FieldAccess {
+                    base: Box::new(value.clone()),
+                    field,
+                })
+            }
+            _ => return Ok(value),
+        };
+        let span = lexer.span_from(&value.span);
+        value = Expr::new(kind, span);
     }
 }
 
-pub fn array_indexer(lexer: &mut Lexer) -> Expr {
-    lexer.match_token(TokenType::OBracket);
-    let index = expr(lexer);
-    lexer.match_token(TokenType::CBracket);
-    index
+/// Parses an index a[i] or a slice a[start..end] where both ends are optional
+pub fn array_indexer(lexer: &mut Lexer, base: Expr) -> Result<ExprKind, Diagnostic> {
+    lexer.match_token(TokenType::OBracket)?;
+    let start = match lexer.get_token_type()? {
+        TokenType::DoubleDot => None,
+        _ => Some(Box::new(expr(lexer)?)),
+    };
+    let kind = match (lexer.get_token_type()?, start) {
+        (TokenType::DoubleDot, start) => {
+            lexer.match_token(TokenType::DoubleDot)?;
+            let end = match lexer.get_token_type()? {
+                TokenType::CBracket => None,
+                _ => Some(Box::new(expr(lexer)?)),
+            };
+            ExprKind::Slice(Slice {
+                base: Box::new(base),
+                start,
+                end,
+            })
+        }
+        (_, Some(indexer)) => ExprKind::ArrayIndex(ArrayIndex {
+            base: Box::new(base),
+            indexer,
+        }),
+        (t_type, None) => {
+            return Err(lexer.error(format!(
+                "Expected {:?}, found {:?}",
+                TokenType::DoubleDot,
+                t_type
+            )));
+        }
+    };
+    lexer.match_token(TokenType::CBracket)?;
+    Ok(kind)
 }
 
-pub fn function_call_args(lexer: &mut Lexer) -> Vec<Expr> {
+pub fn array_literal(lexer: &mut Lexer) -> Result<Vec<Expr>, Diagnostic> {
+    let mut items = Vec::<Expr>::new();
+    lexer.match_token(TokenType::OBracket)?;
+    loop {
+        //[] | [expr] | [expr, expr,]
+        match lexer.get_token_type()? {
+            TokenType::CBracket => {
+                lexer.match_token(TokenType::CBracket)?;
+                break;
+            }
+            _ => {
+                items.push(expr(lexer)?);
+                if lexer.get_token_type()? != TokenType::CBracket {
+                    lexer.match_token(TokenType::Comma)?;
+                }
+            }
+        }
+    }
+    Ok(items)
+}
+
+pub fn function_call_args(lexer: &mut Lexer) -> Result<Vec<Expr>, Diagnostic> {
     let mut args = Vec::<Expr>::new();
-    lexer.match_token(TokenType::OParen);
+    lexer.match_token(TokenType::OParen)?;
     loop {
         //|| | expr | expr , expr
-        match lexer.get_token_type() {
+        match lexer.get_token_type()? {
             TokenType::CParen => {
-                lexer.match_token(TokenType::CParen);
+                lexer.match_token(TokenType::CParen)?;
                 break;
             }
             _ => {
-                args.push(expr(lexer));
-                if lexer.get_token_type() == TokenType::Comma {
-                    lexer.match_token(TokenType::Comma);
+                args.push(expr(lexer)?);
+                if lexer.get_token_type()? == TokenType::Comma {
+                    lexer.match_token(TokenType::Comma)?;
                 }
             }
         }
     }
-    args
+    Ok(args)
 }
 
-pub fn function_def(lexer: &mut Lexer) -> Function {
-    lexer.match_token(TokenType::Func);
+pub fn function_def(lexer: &mut Lexer) -> Result<Function, Diagnostic> {
+    let start = lexer.get_token().span();
+    lexer.match_token(TokenType::Func)?;
     let function_ident_token = lexer.get_token();
     let mut ret_type: Option<VariableType> = None;
     if function_ident_token.is_empty() {
-        eprintln!(
-            "Function Defenition without Identifier at {}",
-            lexer.get_loc_string()
-        );
-        exit(-1);
+        return Err(lexer.error("Function Defenition without Identifier"));
     }
-    lexer.match_token(TokenType::Identifier);
-    let args = function_def_args(lexer);
-    if lexer.get_token_type() == TokenType::ATSign {
-        ret_type = Some(type_def(lexer));
+    lexer.match_token(TokenType::Identifier)?;
+    let args = function_def_args(lexer)?;
+    if lexer.get_token_type()? == TokenType::ATSign {
+        ret_type = Some(type_def(lexer)?);
     }
-    let block = block(lexer);
-    Function {
+    // Errors in the function point to its signature
+    let span = lexer.span_from(&start);
+    let block = block(lexer)?;
+    Ok(Function {
         ident: function_ident_token.literal,
         ret_type,
         args,
         block,
-    }
+        span,
+    })
 }
 
 /*
@@ -211,329 +341,693 @@ pub fn function_def(lexer: &mut Lexer) -> Function {
  * declare := let Ident = expr;
 */
 
-pub fn if_stmt(lexer: &mut Lexer) -> IFStmt {
-    lexer.match_token(TokenType::If);
-    let condition = expr(lexer);
-    let then_block = block(lexer);
-    if lexer.get_token_type() == TokenType::Else {
-        lexer.match_token(TokenType::Else);
-        if lexer.get_token_type() == TokenType::If {
-            let else_block = Box::new(ElseBlock::Elif(if_stmt(lexer)));
-            IFStmt {
+pub fn if_stmt(lexer: &mut Lexer) -> Result<IFStmt, Diagnostic> {
+    lexer.match_token(TokenType::If)?;
+    let condition = expr(lexer)?;
+    let then_block = block(lexer)?;
+    if lexer.get_token_type()? == TokenType::Else {
+        lexer.match_token(TokenType::Else)?;
+        if lexer.get_token_type()? == TokenType::If {
+            let else_block = Box::new(ElseBlock::Elif(if_stmt(lexer)?));
+            Ok(IFStmt {
                 condition,
                 then_block,
                 else_block,
-            }
+            })
         } else {
-            let else_block = Box::new(ElseBlock::Else(block(lexer)));
-            IFStmt {
+            let else_block = Box::new(ElseBlock::Else(block(lexer)?));
+            Ok(IFStmt {
                 condition,
                 then_block,
                 else_block,
-            }
+            })
         }
     } else {
-        IFStmt {
+        Ok(IFStmt {
             condition,
             then_block,
             else_block: Box::new(ElseBlock::None),
+        })
+    }
+}
+
+pub fn while_stmt(lexer: &mut Lexer) -> Result<WhileStmt, Diagnostic> {
+    lexer.match_token(TokenType::While)?;
+    let condition = expr(lexer)?;
+    let block = block(lexer)?;
+    Ok(WhileStmt { condition, block })
+}
+
+pub fn for_stmt(lexer: &mut Lexer) -> Result<ForStmt, Diagnostic> {
+    lexer.match_token(TokenType::For)?;
+    let ident = lexer.get_token().literal;
+    lexer.match_token(TokenType::Identifier)?;
+    lexer.match_token(TokenType::In)?;
+    let start = expr(lexer)?;
+    let iter = match lexer.get_token_type()? {
+        TokenType::DoubleDot | TokenType::DoubleDotEq => {
+            let inclusive = lexer.get_token_type()? == TokenType::DoubleDotEq;
+            lexer.next_token()?;
+            let end = expr(lexer)?;
+            // step is only a keyword after a range
+            let token = lexer.get_token();
+            let mut step = None;
+            if token.t_type == TokenType::Identifier && token.literal == "step" {
+                lexer.match_token(TokenType::Identifier)?;
+                step = Some(Box::new(expr(lexer)?));
+            }
+            ForIter::Range {
+                start: Box::new(start),
+                end: Box::new(end),
+                inclusive,
+                step,
+            }
         }
+        _ => ForIter::Array(Box::new(start)),
+    };
+    let block = block(lexer)?;
+    Ok(ForStmt {
+        ident,
+        iter,
+        block,
+        v_type: None,
+    })
+}
+
+pub fn match_stmt(lexer: &mut Lexer) -> Result<MatchStmt, Diagnostic> {
+    lexer.match_token(TokenType::Match)?;
+    let expr = expr(lexer)?;
+    let mut arms = Vec::<MatchArm>::new();
+    lexer.match_token(TokenType::OCurly)?;
+    while lexer.get_token_type()? != TokenType::CCurly {
+        let start = lexer.get_token().span();
+        // pattern {, pattern} => block
+        let mut patterns = vec![pattern(lexer)?];
+        while lexer.get_token_type()? == TokenType::Comma {
+            lexer.match_token(TokenType::Comma)?;
+            patterns.push(pattern(lexer)?);
+        }
+        let span = lexer.span_from(&start);
+        lexer.match_token(TokenType::FatArrow)?;
+        let block = block(lexer)?;
+        arms.push(MatchArm {
+            patterns,
+            block,
+            span,
+        });
     }
+    lexer.match_token(TokenType::CCurly)?;
+    Ok(MatchStmt { expr, arms })
 }
 
-pub fn while_stmt(lexer: &mut Lexer) -> WhileStmt {
-    lexer.match_token(TokenType::While);
-    let condition = expr(lexer);
-    let block = block(lexer);
-    WhileStmt { condition, block }
+pub fn pattern(lexer: &mut Lexer) -> Result<Pattern, Diagnostic> {
+    let token = lexer.get_token();
+    if token.t_type == TokenType::Identifier && token.literal == "_" {
+        lexer.match_token(TokenType::Identifier)?;
+        return Ok(Pattern::Default);
+    }
+    let start = expr(lexer)?;
+    let inclusive = match lexer.get_token_type()? {
+        TokenType::DoubleDot => false,
+        TokenType::DoubleDotEq => true,
+        _ => return Ok(Pattern::Value(start)),
+    };
+    lexer.next_token()?;
+    let end = expr(lexer)?;
+    Ok(Pattern::Range {
+        start,
+        end,
+        inclusive,
+    })
 }
 
-pub fn block(lexer: &mut Lexer) -> Block {
-    lexer.match_token(TokenType::OCurly);
+pub fn block(lexer: &mut Lexer) -> Result<Block, Diagnostic> {
+    lexer.match_token(TokenType::OCurly)?;
     let mut stmts = Vec::<Stmt>::new();
     loop {
-        if lexer.get_token_type() == TokenType::CCurly {
-            break;
-        }
-        match lexer.get_token_type() {
-            TokenType::Var => {
-                stmts.push(Stmt::VariableDecl(variable_declare(lexer)));
-                lexer.match_token(TokenType::SemiColon);
+        match lexer.get_token_type()? {
+            TokenType::CCurly => break,
+            // A top level item means the block is never closed
+            TokenType::Func | TokenType::Import | TokenType::Struct | TokenType::Enum => {
+                return Err(lexer.error(format!(
+                    "Expected {:?}, found {:?}",
+                    TokenType::CCurly,
+                    lexer.get_token().t_type
+                )));
             }
-            TokenType::Print => {
-                lexer.match_token(TokenType::Print);
-                let expr = expr(lexer);
-                stmts.push(Stmt::Print(expr));
-                lexer.match_token(TokenType::SemiColon);
-            }
-            TokenType::Break => {
-                lexer.match_token(TokenType::Break);
-                stmts.push(Stmt::Break);
-                lexer.match_token(TokenType::SemiColon);
+            _ => {}
+        }
+        match stmt(lexer) {
+            Ok(stmt) => stmts.push(stmt),
+            Err(err) => {
+                lexer.diagnostics.push(err);
+                synchronize(lexer);
             }
-            TokenType::Continue => {
-                lexer.match_token(TokenType::Continue);
-                stmts.push(Stmt::Continue);
-                lexer.match_token(TokenType::SemiColon);
+        }
+    }
+    lexer.match_token(TokenType::CCurly)?;
+    Ok(Block { stmts })
+}
+
+pub fn stmt(lexer: &mut Lexer) -> Result<Stmt, Diagnostic> {
+    let start = lexer.get_token().span();
+    let kind = match lexer.get_token_type()? {
+        TokenType::Var => {
+            let kind = StmtKind::VariableDecl(variable_declare(lexer)?);
+            lexer.match_token(TokenType::SemiColon)?;
+            kind
+        }
+        TokenType::Print | TokenType::Put => {
+            let newline = lexer.get_token_type()? == TokenType::Print;
+            lexer.next_token()?;
+            let expr = expr(lexer)?;
+            let mut format = None;
+            if lexer.get_token_type()? == TokenType::Comma {
+                lexer.match_token(TokenType::Comma)?;
+                let token = lexer.get_token();
+                lexer.match_token(TokenType::Identifier)?;
+                format = Some(PrintFormat::from_string(&token.literal).ok_or_else(|| {
+                    Diagnostic::error(format!("Unknown print format ({})", token.literal))
+                        .with_span(token.span())
+                        .with_note("Expected one of dec, hex, bin or char")
+                })?);
             }
-            TokenType::If => {
-                stmts.push(Stmt::If(if_stmt(lexer)));
+            lexer.match_token(TokenType::SemiColon)?;
+            StmtKind::Print(PrintStmt {
+                expr,
+                format,
+                newline,
+            })
+        }
+        TokenType::Panic => {
+            lexer.match_token(TokenType::Panic)?;
+            lexer.match_token(TokenType::OParen)?;
+            let message = expr(lexer)?;
+            lexer.match_token(TokenType::CParen)?;
+            lexer.match_token(TokenType::SemiColon)?;
+            StmtKind::Panic(message)
+        }
+        TokenType::Assert => {
+            lexer.match_token(TokenType::Assert)?;
+            lexer.match_token(TokenType::OParen)?;
+            let condition = expr(lexer)?;
+            let mut message = None;
+            if lexer.get_token_type()? == TokenType::Comma {
+                lexer.match_token(TokenType::Comma)?;
+                message = Some(expr(lexer)?);
             }
-            TokenType::While => {
-                stmts.push(Stmt::While(while_stmt(lexer)));
+            lexer.match_token(TokenType::CParen)?;
+            lexer.match_token(TokenType::SemiColon)?;
+            StmtKind::Assert(AssertStmt { condition, message })
+        }
+        TokenType::Break => {
+            lexer.match_token(TokenType::Break)?;
+            lexer.match_token(TokenType::SemiColon)?;
+            StmtKind::Break
+        }
+        TokenType::Continue => {
+            lexer.match_token(TokenType::Continue)?;
+            lexer.match_token(TokenType::SemiColon)?;
+            StmtKind::Continue
+        }
+        TokenType::If => StmtKind::If(if_stmt(lexer)?),
+        TokenType::While => StmtKind::While(while_stmt(lexer)?),
+        TokenType::Match => StmtKind::Match(match_stmt(lexer)?),
+        TokenType::For => StmtKind::For(for_stmt(lexer)?),
+        TokenType::Return => {
+            lexer.match_token(TokenType::Return)?;
+            let value = match lexer.get_token_type()? {
+                TokenType::SemiColon => None,
+                _ => Some(expr(lexer)?),
+            };
+            lexer.match_token(TokenType::SemiColon)?;
+            StmtKind::Return(value)
+        }
+        TokenType::Identifier | TokenType::Multi | TokenType::OParen => {
+            //Assgin Op
+            let left_expr = expr(lexer)?;
+            let token_type = lexer.get_token_type()?;
+            let kind = if token_type == TokenType::SemiColon {
+                StmtKind::Expr(left_expr)
+            } else if token_type.is_assgin_token() {
+                let op_type = AssginOp::from_token_type(&token_type);
+                lexer.match_token(token_type)?;
+                let right_expr = expr(lexer)?;
+                StmtKind::Assgin(Assgin {
+                    left: left_expr,
+                    right: right_expr,
+                    op: op_type,
+                })
+            } else {
+                return Err(lexer.error(format!("Expected Semicolon found ({:?})", token_type)));
+            };
+            lexer.match_token(TokenType::SemiColon)?;
+            kind
+        }
+        TokenType::Asm => {
+            lexer.match_token(TokenType::Asm)?;
+            lexer.match_token(TokenType::OCurly)?;
+            let mut instructs = Vec::<String>::new();
+            while lexer.get_token_type()? == TokenType::String {
+                instructs.push(lexer.get_token().literal);
+                lexer.match_token(TokenType::String)?;
             }
-            TokenType::Return => {
-                lexer.match_token(TokenType::Return);
-                stmts.push(Stmt::Return(expr(lexer)));
-                lexer.match_token(TokenType::SemiColon);
+            lexer.match_token(TokenType::CCurly)?;
+            StmtKind::InlineAsm(instructs)
+        }
+        t_type => {
+            return Err(lexer.error(format!(
+                "Unexpected Token ({:?}) at the start of a statement",
+                t_type
+            )))
+        }
+    };
+    Ok(Stmt {
+        kind,
+        span: lexer.span_from(&start),
+    })
+}
+
+/// Skips tokens after a syntax error until a point where parsing can continue
+/// Stops after a ";" or before a "}" and the func, var and import keywords
+/// A nested "{ ... }" is skipped as a whole so its "}" does not end the enclosing block
+pub fn synchronize(lexer: &mut Lexer) {
+    let skip = |lexer: &mut Lexer| {
+        if let Err(err) = lexer.next_token() {
+            lexer.diagnostics.push(err);
+        }
+    };
+    let mut depth = 0;
+    loop {
+        match lexer.get_token().t_type {
+            TokenType::OCurly => {
+                depth += 1;
+                skip(lexer);
             }
-            TokenType::Identifier => {
-                //Assgin Op
-                let left_expr = expr(lexer);
-                let token_type = lexer.get_token_type();
-                if token_type == TokenType::SemiColon {
-                    stmts.push(Stmt::Expr(left_expr));
-                } else if token_type.is_assgin_token() {
-                    let op_type = AssginOp::from_token_type(&token_type);
-                    lexer.match_token(token_type);
-                    let right_expr = expr(lexer);
-                    stmts.push(Stmt::Assgin(Assgin {
-                        left: left_expr,
-                        right: right_expr,
-                        op: op_type,
-                    }));
-                } else {
-                    eprintln!("Error: Expected Semicolon at {}", lexer.get_loc_string());
-                    exit(-1);
-                }
-                lexer.match_token(TokenType::SemiColon);
-            }
-            TokenType::Asm => {
-                lexer.match_token(TokenType::Asm);
-                lexer.match_token(TokenType::OCurly);
-                let mut instructs = Vec::<String>::new();
-                while lexer.get_token_type() == TokenType::String {
-                    instructs.push(lexer.get_token().literal);
-                    lexer.match_token(TokenType::String);
+            TokenType::CCurly if depth > 0 => {
+                depth -= 1;
+                skip(lexer);
+                if depth == 0 {
+                    return;
                 }
-                lexer.match_token(TokenType::CCurly);
-                stmts.push(Stmt::InlineAsm(instructs));
             }
-            _ => {
-                todo!();
+            TokenType::SemiColon if depth == 0 => {
+                skip(lexer);
+                return;
             }
+            TokenType::Var if depth == 0 => return,
+            TokenType::CCurly
+            | TokenType::Func
+            | TokenType::Import
+            | TokenType::Struct
+            | TokenType::Enum
+            | TokenType::Eof => return,
+            _ => skip(lexer),
         }
     }
-    lexer.match_token(TokenType::CCurly);
-    Block { stmts }
 }
 
-pub fn type_def(lexer: &mut Lexer) -> VariableType {
-    lexer.match_token(TokenType::ATSign);
-    match lexer.get_token_type() {
+pub fn type_def(lexer: &mut Lexer) -> Result<VariableType, Diagnostic> {
+    lexer.match_token(TokenType::ATSign)?;
+    match lexer.get_token_type()? {
         TokenType::Ptr => {
-            lexer.match_token(TokenType::Ptr);
-            VariableType::Pointer
+            // @ptr | @ptr int | @ptr @[int,3]
+            lexer.match_token(TokenType::Ptr)?;
+            let pointee = match lexer.get_token_type()? {
+                TokenType::Identifier => {
+                    let ident = lexer.get_token().literal;
+                    lexer.match_token(TokenType::Identifier)?;
+                    Some(Box::new(VariableType::from_string(ident)))
+                }
+                TokenType::ATSign => Some(Box::new(type_def(lexer)?)),
+                _ => None,
+            };
+            Ok(VariableType::Pointer(pointee))
         }
         TokenType::Identifier => {
             let ident = lexer.get_token().literal;
-            lexer.match_token(TokenType::Identifier);
-            VariableType::from_string(ident)
+            lexer.match_token(TokenType::Identifier)?;
+            Ok(VariableType::from_string(ident))
         }
         TokenType::OBracket => {
             let var_type: VariableType;
             let size: usize;
-            lexer.match_token(TokenType::OBracket);
+            lexer.match_token(TokenType::OBracket)?;
             let token = lexer.get_token();
             if token.is_empty() {
-                eprintln!(
-                    "Error: Expected an Identifier found EOF at {}",
-                    lexer.get_loc_string()
-                );
-                exit(1);
+                return Err(lexer.error("Expected an Identifier found EOF"));
             }
             if token.t_type == TokenType::Identifier {
                 var_type = VariableType::from_string(lexer.get_token().literal);
-                lexer.match_token(TokenType::Identifier);
+                lexer.match_token(TokenType::Identifier)?;
             } else if token.t_type == TokenType::ATSign {
-                var_type = self::type_def(lexer);
+                var_type = self::type_def(lexer)?;
             } else {
-                eprintln!(
-                    "Error: Expected Identifier found {:?}, at {}",
-                    lexer.get_token_type(),
-                    lexer.get_loc_string()
-                );
-                exit(1);
-            }
-            lexer.match_token(TokenType::Comma);
+                return Err(lexer.error(format!("Expected Identifier found {:?}", token.t_type)));
+            }
+            // @[int] is a slice and a slice of chars is a string
+            if lexer.get_token_type()? == TokenType::CBracket {
+                lexer.match_token(TokenType::CBracket)?;
+                return match var_type {
+                    VariableType::Char => Ok(VariableType::String),
+                    t => Ok(VariableType::Slice(Box::new(t))),
+                };
+            }
+            lexer.match_token(TokenType::Comma)?;
             let token = lexer.get_token();
             if token.is_empty() {
-                eprintln!(
-                    "Error: Expected a Number found EOF at {}",
-                    lexer.get_loc_string()
-                );
-                exit(1);
+                return Err(lexer.error("Expected a Number found EOF"));
             }
             match token.t_type {
                 TokenType::Int(s) => {
                     size = s as usize;
-                    lexer.match_token(TokenType::Int(s));
+                    lexer.match_token(TokenType::Int(s))?;
                 }
                 TokenType::QMark => {
-                    lexer.match_token(TokenType::QMark);
-                    return VariableType::Pointer;
+                    lexer.match_token(TokenType::QMark)?;
+                    lexer.match_token(TokenType::CBracket)?;
+                    return Ok(VariableType::Pointer(Some(Box::new(var_type))));
                 }
-                _ => {
-                    eprintln!(
-                        "Error: Expected Integer Number found {:?}, at {}",
-                        lexer.get_token_type(),
-                        lexer.get_loc_string()
-                    );
-                    exit(1);
+                t_type => {
+                    return Err(lexer.error(format!("Expected Integer Number found {:?}", t_type)));
                 }
             }
-            lexer.match_token(TokenType::CBracket);
-            VariableType::Array(Box::new(var_type), size)
-        }
-        _ => {
-            eprintln!("Syntax Error: Unknown Token at {}", lexer.get_loc_string());
-            exit(1);
+            lexer.match_token(TokenType::CBracket)?;
+            Ok(VariableType::Array(Box::new(var_type), size))
         }
+        t_type => Err(lexer.error(format!("Unknown Token ({:?}) for type definition", t_type))),
     }
 }
 
-pub fn variable_declare(lexer: &mut Lexer) -> VariableDeclare {
-    lexer.match_token(TokenType::Var);
+pub fn variable_declare(lexer: &mut Lexer) -> Result<VariableDeclare, Diagnostic> {
+    let start = lexer.get_token().span();
+    lexer.match_token(TokenType::Var)?;
     let ident_token = lexer.get_token();
-    lexer.match_token(TokenType::Identifier);
+    lexer.match_token(TokenType::Identifier)?;
     let mut is_mutable: bool = true;
     let mut is_static: bool = false;
     let mut v_type: Option<VariableType> = None;
     let mut init_value: Option<Expr> = None;
-    if lexer.get_token_type() == TokenType::ATSign {
-        v_type = Some(type_def(lexer));
+    if lexer.get_token_type()? == TokenType::ATSign {
+        v_type = Some(type_def(lexer)?);
     }
-    match lexer.get_token_type() {
+    match lexer.get_token_type()? {
         TokenType::DoubleColon => {
             is_static = true;
             is_mutable = false;
-            lexer.match_token(TokenType::ColonEq);
-            init_value = Some(expr(lexer));
+            lexer.match_token(TokenType::DoubleColon)?;
+            init_value = Some(expr(lexer)?);
         }
         TokenType::ColonEq => {
             is_mutable = false;
-            lexer.match_token(TokenType::ColonEq);
-            init_value = Some(expr(lexer));
+            lexer.match_token(TokenType::ColonEq)?;
+            init_value = Some(expr(lexer)?);
         }
         TokenType::Eq => {
             is_mutable = true;
-            lexer.match_token(TokenType::Eq);
-            init_value = Some(expr(lexer));
+            lexer.match_token(TokenType::Eq)?;
+            init_value = Some(expr(lexer)?);
         }
         TokenType::SemiColon => {}
-        _ => {
-            eprintln!(
-                "Error: Expected \"=\" or \":=\" found ({:?}) at {}",
-                lexer.get_token_type(),
-                lexer.get_loc_string()
-            );
-            exit(-1);
+        t_type => {
+            return Err(lexer.error(format!("Expected \"=\" or \":=\" found ({:?})", t_type)));
         }
     }
-    VariableDeclare {
+    Ok(VariableDeclare {
         mutable: is_mutable,
         is_static,
         ident: ident_token.literal,
         v_type,
         init_value,
-    }
+        span: lexer.span_from(&start),
+    })
 }
 
-pub fn function_def_args(lexer: &mut Lexer) -> Vec<FunctionArg> {
+pub fn function_def_args(lexer: &mut Lexer) -> Result<Vec<FunctionArg>, Diagnostic> {
     let mut args = Vec::<FunctionArg>::new();
-    lexer.match_token(TokenType::OParen);
+    lexer.match_token(TokenType::OParen)?;
     loop {
-        match lexer.get_token_type() {
+        match lexer.get_token_type()? {
             TokenType::CParen => {
-                lexer.match_token(TokenType::CParen);
+                lexer.match_token(TokenType::CParen)?;
                 break;
             }
             TokenType::Identifier => {
+                let start = lexer.get_token().span();
                 let ident = lexer.get_token().literal;
-                lexer.match_token(TokenType::Identifier);
-                let typedef = type_def(lexer);
-                if lexer.get_token_type() == TokenType::Comma {
-                    lexer.match_token(TokenType::Comma);
+                lexer.match_token(TokenType::Identifier)?;
+                let typedef = type_def(lexer)?;
+                let span = lexer.span_from(&start);
+                if lexer.get_token_type()? == TokenType::Comma {
+                    lexer.match_token(TokenType::Comma)?;
                 }
                 args.push(FunctionArg {
                     ident: ident.to_string(),
                     typedef,
+                    span,
                 });
             }
-            _ => {
-                eprintln!(
-                    "Error: Expected Identifier found ({:?}) at {}",
-                    lexer.get_token_type(),
-                    lexer.get_loc_string()
-                );
-                exit(-1);
+            t_type => {
+                return Err(lexer.error(format!("Expected Identifier found ({:?})", t_type)));
             }
         }
     }
-    args
+    Ok(args)
 }
 
 pub fn program(lexer: &mut Lexer) -> ProgramFile {
-    lexer.next_token();
+    if let Err(err) = lexer.next_token() {
+        lexer.diagnostics.push(err);
+        synchronize(lexer);
+    }
     let mut items = Vec::<ProgramItem>::new();
     loop {
         if lexer.get_token().is_empty() {
             break;
         }
-        match lexer.get_token_type() {
-            TokenType::Func => {
-                items.push(ProgramItem::Func(function_def(lexer)));
+        match program_item(lexer) {
+            Ok(item) => items.push(item),
+            Err(err) => {
+                lexer.diagnostics.push(err);
+                // Skip the broken item until the next top level keyword
+                while !matches!(
+                    lexer.get_token().t_type,
+                    TokenType::Func
+                        | TokenType::Var
+                        | TokenType::Import
+                        | TokenType::Struct
+                        | TokenType::Enum
+                        | TokenType::Eof
+                ) {
+                    if let Err(err) = lexer.next_token() {
+                        lexer.diagnostics.push(err);
+                    }
+                }
+            }
+        }
+    }
+    ProgramFile { items }
+}
+
+pub fn program_item(lexer: &mut Lexer) -> Result<ProgramItem, Diagnostic> {
+    match lexer.get_token_type()? {
+        TokenType::Func => Ok(ProgramItem::Func(function_def(lexer)?)),
+        TokenType::Var => {
+            let var = variable_declare(lexer)?;
+            lexer.match_token(TokenType::SemiColon)?;
+            Ok(ProgramItem::StaticVar(var))
+        }
+        TokenType::Import => import_file(lexer),
+        TokenType::Struct => Ok(ProgramItem::Struct(struct_def(lexer)?)),
+        TokenType::Enum => Ok(ProgramItem::Enum(enum_def(lexer)?)),
+        t_type => Err(lexer.error(format!(
+            "Unexpected Token ({:?}) for top level program",
+            t_type
+        ))),
+    }
+}
+
+pub fn struct_def(lexer: &mut Lexer) -> Result<StructDef, Diagnostic> {
+    let start = lexer.get_token().span();
+    lexer.match_token(TokenType::Struct)?;
+    let ident = lexer.get_token().literal;
+    lexer.match_token(TokenType::Identifier)?;
+    let span = lexer.span_from(&start);
+    let mut fields = Vec::<StructField>::new();
+    lexer.match_token(TokenType::OCurly)?;
+    loop {
+        match lexer.get_token_type()? {
+            TokenType::CCurly => {
+                lexer.match_token(TokenType::CCurly)?;
+                break;
             }
-            TokenType::Var => {
-                items.push(ProgramItem::StaticVar(variable_declare(lexer)));
+            TokenType::Identifier => {
+                let start = lexer.get_token().span();
+                let ident = lexer.get_token().literal;
+                lexer.match_token(TokenType::Identifier)?;
+                let typedef = type_def(lexer)?;
+                let span = lexer.span_from(&start);
+                if lexer.get_token_type()? == TokenType::Comma {
+                    lexer.match_token(TokenType::Comma)?;
+                }
+                fields.push(StructField {
+                    ident,
+                    typedef,
+                    span,
+                });
             }
-            TokenType::Import => items.push(import_file(lexer)),
-            _ => {
-                eprintln!(
-                    "Error: Unexpected Token ({:?}) for top level program at {}",
-                    lexer.get_token_type(),
-                    lexer.get_loc_string()
-                );
-                exit(-1);
+            t_type => {
+                return Err(lexer.error(format!("Expected Identifier found ({:?})", t_type)));
             }
         }
     }
-    ProgramFile {
-        shebang: String::new(),
-        file_path: lexer.file_path.clone(),
-        items,
+    Ok(StructDef {
+        ident,
+        fields,
+        span,
+    })
+}
+
+pub fn enum_def(lexer: &mut Lexer) -> Result<EnumDef, Diagnostic> {
+    let start = lexer.get_token().span();
+    lexer.match_token(TokenType::Enum)?;
+    let ident = lexer.get_token().literal;
+    lexer.match_token(TokenType::Identifier)?;
+    let span = lexer.span_from(&start);
+    let mut variants = Vec::<EnumMember>::new();
+    lexer.match_token(TokenType::OCurly)?;
+    loop {
+        match lexer.get_token_type()? {
+            TokenType::CCurly => {
+                lexer.match_token(TokenType::CCurly)?;
+                break;
+            }
+            TokenType::Identifier => {
+                let start = lexer.get_token().span();
+                let ident = lexer.get_token().literal;
+                lexer.match_token(TokenType::Identifier)?;
+                let mut value = None;
+                if lexer.get_token_type()? == TokenType::Eq {
+                    lexer.match_token(TokenType::Eq)?;
+                    value = Some(expr(lexer)?);
+                }
+                let span = lexer.span_from(&start);
+                if lexer.get_token_type()? == TokenType::Comma {
+                    lexer.match_token(TokenType::Comma)?;
+                }
+                variants.push(EnumMember { ident, value, span });
+            }
+            t_type => {
+                return Err(lexer.error(format!("Expected Identifier found ({:?})", t_type)));
+            }
+        }
     }
+    Ok(EnumDef {
+        ident,
+        variants,
+        span,
+    })
 }
 
-pub fn import_file(lexer: &mut Lexer) -> ProgramItem {
-    lexer.match_token(TokenType::Import);
+pub fn import_file(lexer: &mut Lexer) -> Result<ProgramItem, Diagnostic> {
+    let start = lexer.get_token().span();
+    lexer.match_token(TokenType::Import)?;
     let file_path = lexer.get_token().literal;
-    lexer.match_token(TokenType::String);
-    if lexer.get_token_type() == TokenType::DoubleColon {
-        lexer.match_token(TokenType::DoubleColon);
+    lexer.match_token(TokenType::String)?;
+    if lexer.get_token().t_type == TokenType::DoubleColon {
+        lexer.match_token(TokenType::DoubleColon)?;
         let mut idents_vec = Vec::<String>::new();
         loop {
             let ident = lexer.get_token().literal;
-            lexer.match_token(TokenType::Identifier);
+            lexer.match_token(TokenType::Identifier)?;
             idents_vec.push(ident);
-            if lexer.get_token_type() == TokenType::Comma {
-                lexer.match_token(TokenType::Comma);
+            if lexer.get_token().t_type == TokenType::Comma {
+                lexer.match_token(TokenType::Comma)?;
             } else {
                 break;
             }
         }
-        ProgramItem::Import(file_path, idents_vec)
+        Ok(ProgramItem::Import(
+            file_path,
+            idents_vec,
+            lexer.span_from(&start),
+        ))
     } else {
-        ProgramItem::Import(file_path, vec![])
+        Ok(ProgramItem::Import(
+            file_path,
+            vec![],
+            lexer.span_from(&start),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod parser_tests {
+    use super::{expr, program};
+    use crate::lexer::Lexer;
+    use crate::parser::program::ProgramItem;
+    use crate::parser::stmt::StmtKind;
+
+    #[test]
+    fn recover_from_every_syntax_error() {
+        // An error before a nested block skips the whole block
+        let source = "func a() {\n    if x > { print 1; }\n    while (1 { print 2; }\n    print 3;\n}\nfunc b() {}";
+        let mut lexer = Lexer::new("test.nmt".to_string(), source.to_string());
+        let nested = program(&mut lexer);
+        let lines: Vec<usize> = lexer
+            .diagnostics
+            .iter()
+            .map(|diag| diag.span.as_ref().unwrap().line)
+            .collect();
+        assert_eq!(lines, vec![2, 3]);
+        assert_eq!(nested.items.len(), 2);
+        let ProgramItem::Func(f) = &nested.items[0] else {
+            panic!("Expected a function");
+        };
+        assert!(matches!(
+            f.block.stmts.last().unwrap().kind,
+            StmtKind::Print(_)
+        ));
+
+        let source = "func main() {\n    var a = 3 +;\n    print a;\n    a = = 4;\n}\n} func f() { print 1; }";
+        let mut lexer = Lexer::new("test.nmt".to_string(), source.to_string());
+        let program = program(&mut lexer);
+        let lines: Vec<usize> = lexer
+            .diagnostics
+            .iter()
+            .map(|diag| diag.span.as_ref().unwrap().line)
+            .collect();
+        assert_eq!(lines, vec![2, 4, 6]);
+        assert_eq!(program.items.len(), 2);
+    }
+
+    #[test]
+    fn expr_span_covers_consumed_tokens() {
+        let mut lexer = Lexer::new("test.nmt".to_string(), "\n  a + foo(1, 2) ;".to_string());
+        lexer.next_token().unwrap();
+        let expr = expr(&mut lexer).unwrap();
+        assert_eq!(expr.span.file_path, "test.nmt");
+        assert_eq!((expr.span.line, expr.span.col), (2, 3));
+        assert_eq!((expr.span.end_line, expr.span.end_col), (2, 16));
+    }
+
+    #[test]
+    fn binary_operator_precedence() {
+        let cases = [
+            ("2 + 3 * 4", 14),
+            ("5 - 2 - 1", 2),
+            ("1 << 2 + 1", 8),
+            ("3 > 2 == 1", 1),
+            ("6 & 3 == 3", 0),
+            ("1 | 2 ^ 3 & 1", 3),
+            ("1 || 0 && 0", 1),
+            ("not 0 == 1", 1),
+        ];
+        for (source, value) in cases {
+            let mut lexer = Lexer::new("test.nmt".to_string(), format!("{source};"));
+            lexer.next_token().unwrap();
+            let expr = expr(&mut lexer).unwrap();
+            assert_eq!(expr.const_value(), Some(value), "{source}");
+        }
     }
 }
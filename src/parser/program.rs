@@ -1,11 +1,12 @@
+use crate::diagnostic::Span;
 use crate::parser::function::Function;
 
+use super::enums::EnumDef;
 use super::stmt::VariableDeclare;
+use super::structs::StructDef;
 
 #[derive(Debug, Clone)]
 pub struct ProgramFile {
-    pub shebang: String,
-    pub file_path: String,
     // pub attrs: Vec<Attr>
     pub items: Vec<ProgramItem>,
 }
@@ -14,5 +15,7 @@ pub struct ProgramFile {
 pub enum ProgramItem {
     Func(Function),
     StaticVar(VariableDeclare),
-    Import(String, Vec<String>),
+    Struct(StructDef),
+    Enum(EnumDef),
+    Import(String, Vec<String>, Span),
 }
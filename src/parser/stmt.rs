@@ -1,43 +1,163 @@
+use crate::diagnostic::Span;
 use crate::lexer::TokenType;
 use crate::parser::block::Block;
 use crate::parser::expr::Expr;
+use core::fmt::Display;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum VariableType {
     Custom(String),
     Array(Box<VariableType>, usize),
+    /// Address and length of items of a type, a slice of chars is a String
+    Slice(Box<VariableType>),
     String,
+    /// Signed 64 bit integer, also the type of integer literals
     Int,
-    Pointer,
+    /// Pointer to a type or untyped pointer to bytes
+    Pointer(Option<Box<VariableType>>),
+    /// Unsigned 64 bit integer
     UInt,
+    I8,
+    I16,
+    I32,
+    U8,
+    U16,
+    U32,
+    F32,
+    F64,
     Bool,
     Char,
+    /// Custom type that is resolved to a struct by the checker
+    Struct(String),
+    /// Custom type that is resolved to an enum by the checker
+    Enum(String),
 }
 impl VariableType {
     pub fn from_string(literal: String) -> Self {
         match literal.as_str() {
-            "int" | "i32" => Self::Int,
-            "uint" | "u32" => Self::UInt,
-            "char" | "u8" => Self::Char,
+            "int" | "i64" => Self::Int,
+            "uint" | "u64" => Self::UInt,
+            "i8" => Self::I8,
+            "i16" => Self::I16,
+            "i32" => Self::I32,
+            "u8" => Self::U8,
+            "u16" => Self::U16,
+            "u32" => Self::U32,
+            "f32" => Self::F32,
+            "f64" => Self::F64,
+            "char" => Self::Char,
             "bool" => Self::Bool,
             "str" => Self::String,
-            "ptr" => Self::Pointer,
+            "ptr" => Self::Pointer(None),
             _ => Self::Custom(literal),
         }
     }
+
+    /// Returns true for types that are stored as a plain integer
+    pub fn is_numeric(&self) -> bool {
+        self.int_size().is_some()
+    }
+
+    /// Size of an integer type in bytes
+    /// Returns None if the type is not an integer
+    pub fn int_size(&self) -> Option<usize> {
+        match self {
+            Self::I8 | Self::U8 | Self::Bool | Self::Char => Some(1),
+            Self::I16 | Self::U16 => Some(2),
+            Self::I32 | Self::U32 => Some(4),
+            Self::Int | Self::UInt => Some(8),
+            _ => None,
+        }
+    }
+
+    /// Returns true for types that are stored as an address and a length
+    pub fn is_view(&self) -> bool {
+        matches!(self, Self::String | Self::Slice(_))
+    }
+
+    /// Returns true for floating point types
+    pub fn is_float(&self) -> bool {
+        matches!(self, Self::F32 | Self::F64)
+    }
+
+    /// Returns true for types that support arithmetic
+    pub fn is_arithmetic(&self) -> bool {
+        self.is_numeric() || self.is_float()
+    }
+
+    /// Returns true if values of the type are sign extended and compared as signed
+    pub fn is_signed(&self) -> bool {
+        matches!(
+            self,
+            Self::Int | Self::I8 | Self::I16 | Self::I32 | Self::Enum(_) | Self::Custom(_)
+        )
+    }
+
+    /// Type of a binary operation between two numbers
+    /// Floats win over integers, the wider type wins
+    /// and unsigned wins between integers of the same size
+    pub fn arith_type(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::F64, _) | (_, Self::F64) => return Self::F64,
+            (Self::F32, _) | (_, Self::F32) => return Self::F32,
+            _ => {}
+        }
+        match (self.int_size(), other.int_size()) {
+            (Some(l), Some(r)) if r > l || (r == l && self.is_signed() && !other.is_signed()) => {
+                other.clone()
+            }
+            _ => self.clone(),
+        }
+    }
+}
+impl Display for VariableType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Custom(s) | Self::Struct(s) | Self::Enum(s) => write!(f, "@{s}"),
+            Self::Array(t, s) => write!(f, "@[{},{s}]", t.to_string().trim_start_matches('@')),
+            Self::Slice(t) => write!(f, "@[{}]", t.to_string().trim_start_matches('@')),
+            Self::String => write!(f, "@str"),
+            Self::Int => write!(f, "@int"),
+            Self::Pointer(None) => write!(f, "@ptr"),
+            Self::Pointer(Some(t)) => write!(f, "@ptr {t}"),
+            Self::UInt => write!(f, "@uint"),
+            Self::I8 => write!(f, "@i8"),
+            Self::I16 => write!(f, "@i16"),
+            Self::I32 => write!(f, "@i32"),
+            Self::U8 => write!(f, "@u8"),
+            Self::U16 => write!(f, "@u16"),
+            Self::U32 => write!(f, "@u32"),
+            Self::F32 => write!(f, "@f32"),
+            Self::F64 => write!(f, "@f64"),
+            Self::Bool => write!(f, "@bool"),
+            Self::Char => write!(f, "@char"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Stmt {
+    pub kind: StmtKind,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
-pub enum Stmt {
+pub enum StmtKind {
     // expr
     Expr(Expr),
     VariableDecl(VariableDeclare),
     // expr = expr
     Assgin(Assgin),
-    Print(Expr),
+    Print(PrintStmt),
+    // panic(message)
+    Panic(Expr),
+    // assert(condition, message)
+    Assert(AssertStmt),
     While(WhileStmt),
+    For(ForStmt),
+    Match(MatchStmt),
     If(IFStmt),
-    Return(Expr),
+    Return(Option<Expr>),
     InlineAsm(Vec<String>),
     Break,
     Continue,
@@ -50,7 +170,7 @@ pub struct Assgin {
     pub op: AssginOp,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum AssginOp {
     Eq,
     PlusEq,
@@ -89,6 +209,94 @@ pub enum ElseBlock {
     None,
 }
 
+/// print expr[, format]; or put expr[, format];
+#[derive(Debug, Clone)]
+pub struct PrintStmt {
+    pub expr: Expr,
+    /// Formatted based on the type of the expression if not given
+    pub format: Option<PrintFormat>,
+    /// print ends the output with a newline but put does not
+    pub newline: bool,
+}
+
+/// assert(condition[, message]);
+#[derive(Debug, Clone)]
+pub struct AssertStmt {
+    pub condition: Expr,
+    /// "assertion failed" is reported if not given
+    pub message: Option<Expr>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrintFormat {
+    Dec,
+    Hex,
+    Bin,
+    Char,
+}
+impl PrintFormat {
+    pub fn from_string(format: &str) -> Option<Self> {
+        match format {
+            "dec" => Some(Self::Dec),
+            "hex" => Some(Self::Hex),
+            "bin" => Some(Self::Bin),
+            "char" => Some(Self::Char),
+            _ => None,
+        }
+    }
+}
+
+/// for ident in iter { block }
+#[derive(Debug, Clone)]
+pub struct ForStmt {
+    pub ident: String,
+    pub iter: ForIter,
+    pub block: Block,
+    /// Type of the loop variable resolved by the checker
+    pub v_type: Option<VariableType>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ForIter {
+    // start..end | start..=end [step expr]
+    Range {
+        start: Box<Expr>,
+        end: Box<Expr>,
+        inclusive: bool,
+        step: Option<Box<Expr>>,
+    },
+    // Items of an array
+    Array(Box<Expr>),
+}
+
+/// match expr { pattern, pattern => block ... }
+#[derive(Debug, Clone)]
+pub struct MatchStmt {
+    pub expr: Expr,
+    pub arms: Vec<MatchArm>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+    pub patterns: Vec<Pattern>,
+    pub block: Block,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    // 3 | 'a' | Color::Red
+    Value(Expr),
+    // start..end | start..=end
+    Range {
+        start: Expr,
+        end: Expr,
+        inclusive: bool,
+    },
+    // _
+    Default,
+}
+
 #[derive(Debug, Clone)]
 pub struct WhileStmt {
     pub condition: Expr,
@@ -102,4 +310,5 @@ pub struct VariableDeclare {
     pub ident: String,
     pub v_type: Option<VariableType>,
     pub init_value: Option<Expr>,
+    pub span: Span,
 }
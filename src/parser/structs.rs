@@ -0,0 +1,18 @@
+use crate::diagnostic::Span;
+
+use super::stmt::VariableType;
+
+#[derive(Debug, Clone)]
+pub struct StructField {
+    pub ident: String,
+    pub typedef: VariableType,
+    pub span: Span,
+}
+
+/// struct Name { field @type, ... }
+#[derive(Debug, Clone)]
+pub struct StructDef {
+    pub ident: String,
+    pub fields: Vec<StructField>,
+    pub span: Span,
+}
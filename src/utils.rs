@@ -1,11 +1,10 @@
 pub fn get_program_name(path: impl ToString) -> String {
     let path = path.to_string();
-    return path
-        .split('/')
-        .last()
+    path.split('/')
+        .next_back()
         .unwrap()
         .split('.')
         .next()
         .unwrap()
-        .to_string();
+        .to_string()
 }